@@ -0,0 +1,131 @@
+extern crate test;
+extern crate cs373;
+
+use test::Bencher;
+use cs373::matrix::Matrix;
+
+
+static POSES: uint = 100;
+static LANDMARKS: uint = 20;
+
+
+// information matrix of a full slam problem: a chain of poses, each seeing two landmarks
+fn slam_omega(poses: uint, landmarks: uint) -> Matrix<f32> {
+    let dim = (poses + landmarks) * 2;
+    let mut omega = Matrix::zero(dim, dim);
+    omega[(0, 0)] = 1.0;
+    omega[(1, 1)] = 1.0;
+    for k in range(0u, poses) {
+        let p = k * 2;
+        for &l in [k % landmarks, (k * 7 + 3) % landmarks].iter() {
+            let m = 2 * (poses + l);
+            for b in range(0u, 2u) {
+                omega[(p+b, p+b)] += 1.0;
+                omega[(m+b, m+b)] += 1.0;
+                omega[(p+b, m+b)] -= 1.0;
+                omega[(m+b, p+b)] -= 1.0;
+            }
+        }
+        if k + 1 < poses {
+            for b in range(0u, 4u) {
+                omega[(p+b, p+b)] += 1.0;
+            }
+            for b in range(0u, 2u) {
+                omega[(p+b, p+b+2)] -= 1.0;
+                omega[(p+b+2, p+b)] -= 1.0;
+            }
+        }
+    }
+    omega
+}
+
+
+#[bench]
+fn mul(bh: &mut Bencher) {
+    let omega = slam_omega(POSES, LANDMARKS);
+    bh.iter(|| omega * omega);
+}
+
+
+#[bench]
+fn transpose(bh: &mut Bencher) {
+    let omega = slam_omega(POSES, LANDMARKS);
+    bh.iter(|| omega.transpose());
+}
+
+
+#[bench]
+fn cholesky(bh: &mut Bencher) {
+    let omega = slam_omega(POSES, LANDMARKS);
+    bh.iter(|| omega.cholesky(0.00001));
+}
+
+
+#[bench]
+fn spd_inverse(bh: &mut Bencher) {
+    let omega = slam_omega(POSES, LANDMARKS);
+    bh.iter(|| omega.spd_inverse());
+}
+
+
+#[bench]
+fn solve(bh: &mut Bencher) {
+    let omega = slam_omega(POSES, LANDMARKS);
+    let xi = Matrix::from_vec(omega.dimx, 1, Vec::from_fn(omega.dimx, |i| i as f32)).unwrap();
+    bh.iter(|| omega.solve(&xi));
+}
+
+
+// omega, xi and the expand list of one step of ps_6 online_slam with many landmarks
+fn online_slam_setup() -> (Matrix<f32>, Matrix<f32>, Vec<uint>) {
+    let omega = slam_omega(1, 2 * LANDMARKS + 30);
+    let xi = Matrix::from_vec(omega.dimx, 1, Vec::from_fn(omega.dimx, |i| i as f32)).unwrap();
+    let mut list = vec![0, 1];
+    for i in range(4, omega.dimx + 2) {
+        list.push(i);
+    }
+    (omega, xi, list)
+}
+
+
+// expand by a pose, then marginalize the previous one out through the schur complement
+#[bench]
+fn online_slam_step(bh: &mut Bencher) {
+    let (omega, xi, list) = online_slam_setup();
+    let dim = omega.dimx;
+
+    bh.iter(|| {
+        let mut omega = omega.expand(dim + 2, dim + 2, &list, &list);
+        let xi = xi.expand(dim + 2, 1, &list, &vec![0]);
+        for b in range(0u, 4u) {
+            omega[(b, b)] += 1.0;
+        }
+
+        omega.marginalize(&xi, &vec![0, 1])
+    });
+}
+
+
+// the same step the way online_slam did it before marginalize: take the blocks and
+// multiply through the inverse of the dropped pose's block
+#[bench]
+fn online_slam_step_take(bh: &mut Bencher) {
+    let (omega, xi, list) = online_slam_setup();
+    let dim = omega.dimx;
+
+    bh.iter(|| {
+        let mut omega = omega.expand(dim + 2, dim + 2, &list, &list);
+        let xi = xi.expand(dim + 2, 1, &list, &vec![0]);
+        for b in range(0u, 4u) {
+            omega[(b, b)] += 1.0;
+        }
+
+        let keep = Vec::from_fn(omega.dimx - 2, |u: uint| 2 + u);
+        let a = omega.take(&vec![0, 1], &keep);
+        let b = omega.take(&vec![0, 1], &vec![]);
+        let c = xi.take(&vec![0, 1], &vec![0]);
+        let b_inv = b.inverse();
+        (omega.take(&keep, &vec![]) - a.transpose() * b_inv * a,
+         xi.take(&keep, &vec![0]) - a.transpose() * b_inv * c)
+    });
+}
@@ -0,0 +1,81 @@
+use std::num::Float;
+use std::rand::Rng;
+use particle::log_sum_exp;
+use pose::Pose;
+
+
+// Draws a state with no regard to any measurement, for random particle injection.
+pub trait StateSampler<S> {
+    fn sample<R: Rng>(&self, rng: &mut R) -> S;
+}
+
+
+// Augmented MCL (Thrun et al., Probabilistic Robotics 8.3.5). Keeps a short- and a
+// long-term average of how well the particles explain the measurements; when the short
+// one falls well below the long one the robot has probably been moved, and random
+// particles are injected so it can be found again. The averages are kept as logs.
+#[deriving(Show,Clone)]
+pub struct Augmented {
+    pub alpha_slow: f64,
+    pub alpha_fast: f64,
+    log_w_slow: f64,
+    log_w_fast: f64,
+}
+
+
+impl Augmented {
+    // 0 < alpha_slow << alpha_fast <= 1
+    pub fn new(alpha_slow: f64, alpha_fast: f64) -> Augmented {
+        assert!(0.0 < alpha_slow && alpha_slow < alpha_fast && alpha_fast <= 1.0);
+        Augmented {
+            alpha_slow: alpha_slow,
+            alpha_fast: alpha_fast,
+            log_w_slow: Float::neg_infinity(),
+            log_w_fast: Float::neg_infinity(),
+        }
+    }
+
+    // fold in the log mean likelihood of a measurement
+    pub fn observe(&mut self, log_likelihood: f64) {
+        self.log_w_slow = blend(self.log_w_slow, log_likelihood, self.alpha_slow);
+        self.log_w_fast = blend(self.log_w_fast, log_likelihood, self.alpha_fast);
+    }
+
+    // max(0, 1 - w_fast / w_slow)
+    pub fn injection_probability(&self) -> f64 {
+        let p = 1.0 - (self.log_w_fast - self.log_w_slow).exp();
+        if p > 0.0 { p } else { 0.0 }
+    }
+}
+
+
+// log((1 - alpha) * exp(avg) + alpha * exp(x))
+fn blend(avg: f64, x: f64, alpha: f64) -> f64 {
+    log_sum_exp(&vec![(1.0 - alpha).ln() + avg, alpha.ln() + x])
+}
+
+
+// Poses spread evenly over a rectangle, with any heading.
+#[deriving(Show,Clone)]
+pub struct UniformPose {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+
+impl UniformPose {
+    pub fn new(min: (f32, f32), max: (f32, f32)) -> UniformPose {
+        UniformPose { min: min, max: max }
+    }
+}
+
+
+impl StateSampler<Pose> for UniformPose {
+    fn sample<R: Rng>(&self, rng: &mut R) -> Pose {
+        let (x0, y0) = self.min;
+        let (x1, y1) = self.max;
+        Pose::new(x0 + rng.gen::<f32>() * (x1 - x0),
+                  y0 + rng.gen::<f32>() * (y1 - y0),
+                  rng.gen::<f32>() * Float::two_pi())
+    }
+}
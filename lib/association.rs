@@ -0,0 +1,206 @@
+use std::num::Float;
+use particle::{MeasurementModel, log_sum_exp};
+use pose::{HasPose, angle_diff};
+use models::{log_gaussian, RangeModel, BearingModel, RangeBearingModel, Detection};
+
+
+// How observations that don't say which landmark they came from are matched to the map.
+#[deriving(Show,Clone,PartialEq)]
+pub enum Association {
+    // each particle pairs every observation with its most likely landmark, no landmark
+    // being used twice while any are left (greedy nearest neighbour)
+    MaximumLikelihood,
+    // each observation is scored against every landmark, all equally likely a priori, and
+    // against clutter
+    Probabilistic,
+}
+
+
+// A sensor that can score one observation against any one landmark.
+pub trait PerLandmark<S, Z> {
+    fn landmark_count(&self) -> uint;
+    fn log_single(&self, state: &S, z: &Z, landmark: uint) -> f64;
+
+    // log density of an observation, given that it came from no landmark at all
+    fn log_clutter(&self) -> f64 {
+        Float::neg_infinity()
+    }
+
+    // chance that an observation is clutter, before looking at it
+    fn clutter_probability(&self) -> f64 {
+        0.0
+    }
+}
+
+
+impl<S: HasPose> PerLandmark<S, f32> for RangeModel {
+    fn landmark_count(&self) -> uint {
+        self.landmarks.len()
+    }
+
+    fn log_single(&self, state: &S, &z: &f32, landmark: uint) -> f64 {
+        let dist = state.pose().distance_to(self.landmarks[landmark]);
+        log_gaussian(dist as f64, self.noise as f64, z as f64)
+    }
+}
+
+
+impl<S: HasPose> PerLandmark<S, f32> for BearingModel {
+    fn landmark_count(&self) -> uint {
+        self.landmarks.len()
+    }
+
+    fn log_single(&self, state: &S, &z: &f32, landmark: uint) -> f64 {
+        let bearing = state.pose().bearing_to(self.landmarks[landmark]);
+        log_gaussian(0.0, self.noise as f64, angle_diff(z, bearing) as f64)
+    }
+}
+
+
+// Misses aren't scored here, since without labels there is no telling which landmarks
+// were missed. Of the detections in a scan, on average clutter_rate are clutter and
+// p_detect for each landmark are sightings; that ratio is the clutter prior.
+impl<S: HasPose> PerLandmark<S, Detection> for RangeBearingModel {
+    fn landmark_count(&self) -> uint {
+        self.landmarks.len()
+    }
+
+    fn log_single(&self, state: &S, z: &Detection, landmark: uint) -> f64 {
+        self.log_sighting(state, z, landmark)
+    }
+
+    fn log_clutter(&self) -> f64 {
+        if self.clutter_density() == 0.0 {
+            return Float::neg_infinity();
+        }
+        -(self.max_range as f64 * self.fov as f64).ln()
+    }
+
+    fn clutter_probability(&self) -> f64 {
+        if self.clutter_density() == 0.0 {
+            return 0.0;
+        }
+        let sightings = self.p_detect as f64 * self.landmarks.len() as f64;
+        self.clutter_rate as f64 / (self.clutter_rate as f64 + sightings)
+    }
+}
+
+
+// Wraps a sensor so its scans are taken as anonymous and in no particular order.
+#[deriving(Show,Clone)]
+pub struct Unlabelled<M> {
+    pub sensor: M,
+    pub association: Association,
+}
+
+
+impl<M> Unlabelled<M> {
+    pub fn new(sensor: M, association: Association) -> Unlabelled<M> {
+        Unlabelled { sensor: sensor, association: association }
+    }
+}
+
+
+impl<S, Z, M: PerLandmark<S, Z>> Unlabelled<M> {
+    // log_single for every (observation, landmark) pair, a row per observation
+    pub fn table(&self, state: &S, z: &Vec<Z>) -> Vec<Vec<f64>> {
+        let n = self.sensor.landmark_count();
+        z.iter().map(|zi| range(0, n).map(|j| self.sensor.log_single(state, zi, j)).collect())
+            .collect()
+    }
+
+    // the landmark each observation is matched with under maximum likelihood, None for
+    // those taken as clutter
+    pub fn associate(&self, state: &S, z: &Vec<Z>) -> Vec<Option<uint>> {
+        let (table, clutter) = self.weighed(state, z, 0.0);
+        greedy(&table, clutter)
+    }
+
+    // The table and the clutter log density with their priors added: (1 - p) / n for each
+    // landmark and p for clutter, p being the clutter probability. Maximum likelihood
+    // picks a landmark rather than summing over them, so it passes n = 1.
+    fn weighed(&self, state: &S, z: &Vec<Z>, log_n: f64) -> (Vec<Vec<f64>>, f64) {
+        let p = self.sensor.clutter_probability();
+        let prior = (1.0 - p).ln() - log_n;
+        let table = self.table(state, z).iter().map(|row| {
+            row.iter().map(|&l| l + prior).collect()
+        }).collect();
+        (table, self.sensor.log_clutter() + p.ln())
+    }
+}
+
+
+impl<S, Z, M: PerLandmark<S, Z>> MeasurementModel<S, Vec<Z>> for Unlabelled<M> {
+    fn log_likelihood(&self, state: &S, z: &Vec<Z>) -> f64 {
+        match self.association {
+            MaximumLikelihood => {
+                let (table, clutter) = self.weighed(state, z, 0.0);
+                let mut res = 0.0;
+                for (row, a) in table.iter().zip(greedy(&table, clutter).iter()) {
+                    res += match *a {
+                        Some(j) => row[j],
+                        None => clutter,
+                    };
+                }
+                res
+            }
+            Probabilistic => {
+                let log_n = (self.sensor.landmark_count() as f64).ln();
+                let (table, clutter) = self.weighed(state, z, log_n);
+                let mut res = 0.0;
+                for row in table.iter() {
+                    let mut terms = row.clone();
+                    terms.push(clutter);
+                    res += log_sum_exp(&terms);
+                }
+                res
+            }
+        }
+    }
+}
+
+
+// Repeatedly matches the most likely remaining (observation, landmark) pair. Once the
+// landmarks run out the rest take their best one regardless; any observation better
+// explained as clutter is left unmatched.
+fn greedy(table: &Vec<Vec<f64>>, clutter: f64) -> Vec<Option<uint>> {
+    let n = if table.len() > 0 { table[0].len() } else { 0 };
+    let mut res: Vec<Option<uint>> = Vec::from_elem(table.len(), None);
+    let mut done = Vec::from_elem(table.len(), false);
+    let mut used = Vec::from_elem(n, false);
+    loop {
+        let mut best = None;
+        let mut best_ll = clutter;
+        for i in range(0, table.len()) {
+            for j in range(0, n) {
+                if !done[i] && !used[j] && table[i][j] > best_ll {
+                    best = Some((i, j));
+                    best_ll = table[i][j];
+                }
+            }
+        }
+        match best {
+            Some((i, j)) => {
+                *res.get_mut(i) = Some(j);
+                *done.get_mut(i) = true;
+                *used.get_mut(j) = true;
+            }
+            None => break,
+        }
+    }
+    if used.iter().all(|&u| u) {
+        for i in range(0, table.len()) {
+            if done[i] {
+                continue;
+            }
+            let mut best_ll = clutter;
+            for j in range(0, n) {
+                if table[i][j] > best_ll {
+                    *res.get_mut(i) = Some(j);
+                    best_ll = table[i][j];
+                }
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,120 @@
+use std::num::{Float, FloatMath};
+use std::slice::Found;
+use matrix::{Matrix, descending};
+use pose::{Pose, HasPose, angle_diff};
+
+
+// One mode of a particle cloud: its share of the weight, its weighted mean pose and the
+// covariance of (x, y, orientation) around that mean.
+#[deriving(Show,Clone)]
+pub struct Hypothesis {
+    pub pose: Pose,
+    pub weight: f64,
+    pub covariance: Matrix<f64>,
+}
+
+
+// Groups particles into modes, most weighted first. Particles are binned on an
+// (x, y, orientation) grid, and bins that touch, including across the wrap from 2 pi
+// back to 0, belong to the same mode. Averaging the whole cloud instead puts the estimate
+// between the modes when there is more than one.
+pub fn cluster<S: HasPose>(particles: &Vec<S>, weights: &Vec<f64>, xy_resolution: f32,
+        angle_resolution: f32) -> Vec<Hypothesis> {
+    assert!(particles.len() == weights.len());
+    let poses: Vec<Pose> = particles.iter().map(|p| p.pose()).collect();
+    let angle_bins = (Float::two_pi() / angle_resolution).ceil() as i32;
+    let mut keyed: Vec<((i32, i32, i32), uint)> = poses.iter().enumerate().map(|(i, p)| {
+        (((p.x / xy_resolution).floor() as i32,
+          (p.y / xy_resolution).floor() as i32,
+          (p.orientation / angle_resolution).floor() as i32 % angle_bins), i)
+    }).collect();
+    keyed.sort();
+    // occupied bins, sorted, and the particles in each
+    let mut keys: Vec<(i32, i32, i32)> = Vec::new();
+    let mut bins: Vec<Vec<uint>> = Vec::new();
+    for &(key, i) in keyed.iter() {
+        if keys.len() == 0 || keys[keys.len() - 1] != key {
+            keys.push(key);
+            bins.push(Vec::new());
+        }
+        let last = bins.len() - 1;
+        bins.get_mut(last).push(i);
+    }
+
+    let total = weights.iter().fold(0.0, |a, &w| a + w);
+    let mut seen = Vec::from_elem(keys.len(), false);
+    let mut res = Vec::new();
+    for start in range(0, keys.len()) {
+        if seen[start] {
+            continue;
+        }
+        // flood fill over neighbouring occupied bins
+        *seen.get_mut(start) = true;
+        let mut members = Vec::new();
+        let mut stack = vec![start];
+        loop {
+            let b = match stack.pop() {
+                Some(b) => b,
+                None => break,
+            };
+            members.push_all(bins[b].as_slice());
+            let (bx, by, ba) = keys[b];
+            for dx in range(-1i32, 2) {
+                for dy in range(-1i32, 2) {
+                    for da in range(-1i32, 2) {
+                        let key = (bx + dx, by + dy, (ba + da + angle_bins) % angle_bins);
+                        match keys.binary_search(|k| k.cmp(&key)) {
+                            Found(n) if !seen[n] => {
+                                *seen.get_mut(n) = true;
+                                stack.push(n);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        res.push(hypothesis(&poses, weights, &members, total));
+    }
+    res.sort_by(|a, b| descending(&a.weight, &b.weight));
+    res
+}
+
+
+// weighted mean and covariance of the members, with a circular mean for orientation
+fn hypothesis(poses: &Vec<Pose>, weights: &Vec<f64>, members: &Vec<uint>,
+        total: f64) -> Hypothesis {
+    let (mut w, mut x, mut y, mut s, mut c) = (0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64);
+    for &i in members.iter() {
+        let (p, wi) = (&poses[i], weights[i]);
+        w += wi;
+        x += wi * p.x as f64;
+        y += wi * p.y as f64;
+        s += wi * (p.orientation as f64).sin();
+        c += wi * (p.orientation as f64).cos();
+    }
+    let mean = if w > 0.0 {
+        Pose::new((x / w) as f32, (y / w) as f32, s.atan2(c) as f32)
+    } else {
+        poses[members[0]].clone()
+    };
+
+    let mut covariance = Matrix::zero(3, 3);
+    if w > 0.0 {
+        for &i in members.iter() {
+            let p = &poses[i];
+            let d = [(p.x - mean.x) as f64, (p.y - mean.y) as f64,
+                     angle_diff(p.orientation, mean.orientation) as f64];
+            for r in range(0u, 3) {
+                for k in range(0u, 3) {
+                    covariance[(r, k)] = covariance[(r, k)] + weights[i] / w * d[r] * d[k];
+                }
+            }
+        }
+    }
+    Hypothesis {
+        pose: mean,
+        weight: if total > 0.0 { w / total } else { 0.0 },
+        covariance: covariance,
+    }
+}
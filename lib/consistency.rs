@@ -0,0 +1,131 @@
+use std::iter::AdditiveIterator;
+use std::rand::{Rng, SeedableRng, StdRng};
+use std::rand::distributions::{Normal, IndependentSample};
+use matrix::Matrix;
+use kalman::{LinearModel, KalmanFilter};
+use stats::chi2_quantile;
+
+
+#[deriving(Show,Clone)]
+pub struct Bounds {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+
+impl Bounds {
+    // two-sided (1 - alpha) bounds on the mean of `samples` chi-square samples of `dof` each
+    pub fn chi2_average(alpha: f64, dof: uint, samples: uint) -> Bounds {
+        let k = (dof * samples) as f64;
+        Bounds {
+            lower: chi2_quantile(alpha / 2.0, k) / (samples as f64),
+            upper: chi2_quantile(1.0 - alpha / 2.0, k) / (samples as f64),
+        }
+    }
+
+    pub fn contains(&self, v: f64) -> bool {
+        v >= self.lower && v <= self.upper
+    }
+
+    pub fn fraction_inside(&self, values: &Vec<f64>) -> f64 {
+        let inside = values.iter().filter(|&&v| self.contains(v)).count();
+        inside as f64 / values.len() as f64
+    }
+}
+
+
+// Normalized estimation error squared (NEES) and innovation squared (NIS) of a filter
+// over many Monte Carlo runs (Bar-Shalom et al. 5.4). A consistent filter's errors match
+// the covariances it reports, so both are chi-square distributed.
+//
+// The estimation errors of one run are correlated from step to step, so NEES is judged
+// at the last step, across the independent runs. The innovations of a consistent filter
+// are white, so NIS can be averaged over the steps as well.
+#[deriving(Show,Clone)]
+pub struct Verdict {
+    pub nees: Vec<f64>,          // NEES averaged over the runs, per step
+    pub nis: Vec<f64>,           // NIS averaged over the runs, per step
+    pub nees_bounds: Bounds,     // bounds on each step's average
+    pub nis_bounds: Bounds,
+    pub average_nis: f64,        // NIS averaged over runs and steps
+    pub average_nis_bounds: Bounds,
+}
+
+
+impl Verdict {
+    pub fn final_nees(&self) -> f64 {
+        self.nees[self.nees.len() - 1]
+    }
+
+    pub fn nees_consistent(&self) -> bool {
+        self.nees_bounds.contains(self.final_nees())
+    }
+
+    pub fn nis_consistent(&self) -> bool {
+        self.average_nis_bounds.contains(self.average_nis)
+    }
+
+    pub fn passed(&self) -> bool {
+        self.nees_consistent() && self.nis_consistent()
+    }
+}
+
+
+// Runs `runs` seeded simulations of `truth` and filters them with a Kalman filter using
+// `filter`, starting from x0 and p0, which the true initial state is drawn from.
+pub fn evaluate(truth: &LinearModel, filter: &LinearModel, x0: &Matrix<f32>, p0: &Matrix<f32>,
+        runs: uint, steps: uint, alpha: f64, seed: uint) -> Verdict {
+    assert!(runs > 0 && steps > 0);
+    let mut nees = Vec::from_elem(steps, 0.0f64);
+    let mut nis = Vec::from_elem(steps, 0.0f64);
+
+    for run in range(0, runs) {
+        let mut rng: StdRng = SeedableRng::from_seed([seed, run].as_slice());
+
+        let mut truth_x = sample_gaussian(&mut rng, x0, p0);
+        let mut kf = KalmanFilter::new(x0, p0);
+
+        for n in range(0, steps) {
+            // simulate
+            truth_x = sample_gaussian(&mut rng, &(truth.f * truth_x + truth.u), &truth.q);
+            let z = sample_gaussian(&mut rng, &(truth.h * truth_x), &truth.r);
+
+            kf.predict(filter);
+            let (y, s) = kf.update(filter, &z);
+
+            *nis.get_mut(n) += mahalanobis(&y, &s);
+            *nees.get_mut(n) += mahalanobis(&(truth_x - kf.x), &kf.p);
+        }
+    }
+
+    for n in range(0, steps) {
+        *nees.get_mut(n) /= runs as f64;
+        *nis.get_mut(n) /= runs as f64;
+    }
+
+    let dim_x = x0.dimx;
+    let dim_z = truth.h.dimx;
+    Verdict {
+        average_nis: nis.iter().map(|&x| x).sum() / steps as f64,
+        nees: nees,
+        nis: nis,
+        nees_bounds: Bounds::chi2_average(alpha, dim_x, runs),
+        nis_bounds: Bounds::chi2_average(alpha, dim_z, runs),
+        average_nis_bounds: Bounds::chi2_average(alpha, dim_z, runs * steps),
+    }
+}
+
+
+fn sample_gaussian<R: Rng>(rng: &mut R, mean: &Matrix<f32>, cov: &Matrix<f32>) -> Matrix<f32> {
+    let normal = Normal::new(0.0, 1.0);
+    let n = Matrix::new(Vec::from_fn(cov.dimx, |_| vec![normal.ind_sample(rng) as f32])).unwrap();
+    // cov = trans(u) * u
+    let u = cov.cholesky(0.00001);
+    *mean + u.transpose() * n
+}
+
+
+// x' P^-1 x for a column vector x
+fn mahalanobis(x: &Matrix<f32>, p: &Matrix<f32>) -> f64 {
+    (x.transpose() * p.solve(x).unwrap())[(0, 0)] as f64
+}
@@ -0,0 +1,115 @@
+use std::num::{Float, zero, one, cast};
+use matrix::{Matrix, MatrixError, DimensionMismatch, NotSymmetric, lit, rotate_rows, descending};
+
+
+// sweeps before giving up on convergence; jacobi needs well under ten in practice
+static MAX_SWEEPS: uint = 50;
+
+
+// a = v diag(values) trans(v) for symmetric a, by cyclic jacobi rotations. Column k of
+// `vectors` belongs to values[k]; values are sorted largest first.
+#[deriving(Show,Clone)]
+pub struct SymmetricEigen<T> {
+    values: Vec<T>,
+    vectors: Matrix<T>,
+}
+
+
+impl<T: Float> SymmetricEigen<T> {
+    pub fn new(a: &Matrix<T>) -> Result<SymmetricEigen<T>, MatrixError> {
+        if a.dimx != a.dimy {
+            return Err(DimensionMismatch((a.dimx, a.dimx), (a.dimx, a.dimy)));
+        }
+        let n = a.dimx;
+        let tol = a.norm_inf() * Float::epsilon() * cast(n).unwrap();
+        for i in range(0, n) {
+            for j in range(i+1, n) {
+                if (a[(i, j)] - a[(j, i)]).abs() > tol {
+                    return Err(NotSymmetric);
+                }
+            }
+        }
+
+        let mut a = a.clone();
+        let mut v = Matrix::identity(n);
+        let total = sum_squares(&a, false);
+
+        for _ in range(0, MAX_SWEEPS) {
+            if sum_squares(&a, true) <= total * Float::epsilon() * Float::epsilon() {
+                break;
+            }
+            for p in range(0, n) {
+                for q in range(p+1, n) {
+                    if a[(p, q)] == zero() {
+                        continue;
+                    }
+                    // rotation that zeroes a[p][q]
+                    let theta = (a[(q, q)] - a[(p, p)]) / (lit::<T>(2.0) * a[(p, q)]);
+                    let t = one::<T>() / (theta.abs() + (theta * theta + one()).sqrt());
+                    let t = if theta < zero() { -t } else { t };
+                    let c = one::<T>() / (t * t + one()).sqrt();
+                    let s = t * c;
+
+                    rotate_cols(&mut a, p, q, c, s);
+                    rotate_rows(&mut a, p, q, c, s);
+                    rotate_cols(&mut v, p, q, c, s);
+                }
+            }
+        }
+
+        let values: Vec<T> = range(0, n).map(|i| a[(i, i)]).collect();
+        let mut order = Vec::from_fn(n, |i| i);
+        order.sort_by(|&i, &j| descending(&values[i], &values[j]));
+
+        let mut vectors = Matrix::zero(n, n);
+        for k in range(0, n) {
+            for i in range(0, n) {
+                vectors[(i, k)] = v[(i, order[k])];
+            }
+        }
+        Ok(SymmetricEigen {
+            values: order.iter().map(|&i| values[i]).collect(),
+            vectors: vectors,
+        })
+    }
+
+    pub fn values(&self) -> Vec<T> {
+        self.values.clone()
+    }
+
+    pub fn vectors(&self) -> Matrix<T> {
+        self.vectors.clone()
+    }
+}
+
+
+// sum of squares of all entries, or of the off-diagonal ones
+fn sum_squares<T: Float>(a: &Matrix<T>, off_diagonal: bool) -> T {
+    let mut s = zero();
+    for i in range(0, a.dimx) {
+        for j in range(0, a.dimy) {
+            if !off_diagonal || i != j {
+                s = s + a[(i, j)] * a[(i, j)];
+            }
+        }
+    }
+    s
+}
+
+
+// columns p, q <- c * col p - s * col q, s * col p + c * col q
+fn rotate_cols<T: Float>(a: &mut Matrix<T>, p: uint, q: uint, c: T, s: T) {
+    for k in range(0, a.dimx) {
+        let akp = a[(k, p)];
+        let akq = a[(k, q)];
+        a[(k, p)] = c * akp - s * akq;
+        a[(k, q)] = s * akp + c * akq;
+    }
+}
+
+
+impl<T: Float> Matrix<T> {
+    pub fn symmetric_eigen(&self) -> Result<SymmetricEigen<T>, MatrixError> {
+        SymmetricEigen::new(self)
+    }
+}
@@ -0,0 +1,148 @@
+use std::num::{Float, FloatMath};
+
+
+// A map of square cells, each free or occupied. Cell (i, j) covers x in
+// [i * resolution, (i + 1) * resolution) and likewise j for y; everything off the map
+// counts as occupied, so rays stop at its edge.
+#[deriving(Show,Clone,PartialEq)]
+pub struct OccupancyGrid {
+    pub width: uint,  // cells along x
+    pub height: uint, // cells along y
+    pub resolution: f32,
+    cells: Vec<bool>,
+}
+
+
+impl OccupancyGrid {
+    pub fn new(width: uint, height: uint, resolution: f32) -> OccupancyGrid {
+        OccupancyGrid {
+            width: width,
+            height: height,
+            resolution: resolution,
+            cells: Vec::from_elem(width * height, false),
+        }
+    }
+
+    // from the exercises' grids, where grid[x][y] is nonzero for an obstacle
+    pub fn from_rows(rows: &Vec<Vec<uint>>, resolution: f32) -> OccupancyGrid {
+        let width = rows.len();
+        let height = if width > 0 { rows[0].len() } else { 0 };
+        let mut grid = OccupancyGrid::new(width, height, resolution);
+        for i in range(0, width) {
+            assert!(rows[i].len() == height);
+            for j in range(0, height) {
+                grid.set(i, j, rows[i][j] != 0);
+            }
+        }
+        grid
+    }
+
+    pub fn set(&mut self, i: uint, j: uint, occupied: bool) {
+        assert!(i < self.width && j < self.height);
+        *self.cells.get_mut(i * self.height + j) = occupied;
+    }
+
+    pub fn is_occupied(&self, i: int, j: int) -> bool {
+        if i < 0 || j < 0 || i as uint >= self.width || j as uint >= self.height {
+            return true;
+        }
+        self.cells[i as uint * self.height + j as uint]
+    }
+
+    // the cell containing a point
+    pub fn cell(&self, x: f32, y: f32) -> (int, int) {
+        ((x / self.resolution).floor() as int, (y / self.resolution).floor() as int)
+    }
+
+    pub fn is_occupied_at(&self, x: f32, y: f32) -> bool {
+        let (i, j) = self.cell(x, y);
+        self.is_occupied(i, j)
+    }
+
+    // Distance from (x, y) along heading until the first occupied cell, or max_range if
+    // there is none that close. Walks the cells the ray crosses one at a time
+    // (Amanatides & Woo), so thin walls are never stepped over.
+    pub fn ray_cast(&self, x: f32, y: f32, heading: f32, max_range: f32) -> f32 {
+        let (mut i, mut j) = self.cell(x, y);
+        if self.is_occupied(i, j) {
+            return 0.0;
+        }
+        let (dx, dy) = (heading.cos(), heading.sin());
+        let r = self.resolution;
+        let inf: f32 = Float::infinity();
+        // distance along the ray to the next vertical / horizontal cell boundary, and
+        // between successive ones
+        let (step_i, mut next_x, delta_x) = if dx > 0.0 {
+            (1, ((i + 1) as f32 * r - x) / dx, r / dx)
+        } else if dx < 0.0 {
+            (-1, (i as f32 * r - x) / dx, -r / dx)
+        } else {
+            (0, inf, inf)
+        };
+        let (step_j, mut next_y, delta_y) = if dy > 0.0 {
+            (1, ((j + 1) as f32 * r - y) / dy, r / dy)
+        } else if dy < 0.0 {
+            (-1, (j as f32 * r - y) / dy, -r / dy)
+        } else {
+            (0, inf, inf)
+        };
+        loop {
+            let t = if next_x < next_y {
+                i += step_i;
+                let t = next_x;
+                next_x += delta_x;
+                t
+            } else {
+                j += step_j;
+                let t = next_y;
+                next_y += delta_y;
+                t
+            };
+            if t >= max_range {
+                return max_range;
+            }
+            if self.is_occupied(i, j) {
+                return t;
+            }
+        }
+    }
+
+    // For every cell, the distance from its centre to the centre of the nearest occupied
+    // cell, indexed like the cells. Two chamfer passes, so straight and diagonal
+    // distances are exact and the rest are at most 8% too long.
+    pub fn distance_field(&self) -> Vec<f32> {
+        let (w, h) = (self.width as int, self.height as int);
+        let inf: f32 = Float::infinity();
+        let mut d: Vec<f32> = self.cells.iter().map(|&c| if c { 0.0 } else { inf }).collect();
+        let diag = 2.0f32.sqrt();
+        let forward = [(-1i, -1i, diag), (-1, 0, 1.0), (-1, 1, diag), (0, -1, 1.0)];
+        let backward = [(1i, 1i, diag), (1, 0, 1.0), (1, -1, diag), (0, 1, 1.0)];
+        for i in range(0, w) {
+            for j in range(0, h) {
+                relax(&mut d, w, h, i, j, forward.as_slice());
+            }
+        }
+        for i in range(0, w).rev() {
+            for j in range(0, h).rev() {
+                relax(&mut d, w, h, i, j, backward.as_slice());
+            }
+        }
+        d.iter().map(|&c| c * self.resolution).collect()
+    }
+}
+
+
+// d[i, j] = min(d[i, j], d[i + di, j + dj] + cost) over the offsets
+fn relax(d: &mut Vec<f32>, w: int, h: int, i: int, j: int, offsets: &[(int, int, f32)]) {
+    let k = (i * h + j) as uint;
+    for &(di, dj, cost) in offsets.iter() {
+        let (a, b) = (i + di, j + dj);
+        if a < 0 || b < 0 || a >= w || b >= h {
+            continue;
+        }
+        let c = d[(a * h + b) as uint] + cost;
+        if c < d[k] {
+            *d.get_mut(k) = c;
+        }
+    }
+}
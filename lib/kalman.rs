@@ -0,0 +1,79 @@
+use matrix::Matrix;
+
+
+// Linear-gaussian model: x' = f x + u + w, w ~ N(0, q); z = h x + v, v ~ N(0, r)
+#[deriving(Show,Clone)]
+pub struct LinearModel {
+    pub u: Matrix<f32>,             // extern motion
+    pub f: Matrix<f32>,             // next state fn
+    pub f_inv: Option<Matrix<f32>>, // previous state fn, unless f is singular
+    pub q: Matrix<f32>,             // process noise
+    pub h: Matrix<f32>,             // measurement fn
+    pub r: Matrix<f32>,             // measurement uncertainty
+    pub i: Matrix<f32>,             // identity matrix
+}
+
+
+impl LinearModel {
+    pub fn new(f: &Matrix<f32>, q: &Matrix<f32>, h: &Matrix<f32>, r: &Matrix<f32>) -> LinearModel {
+        LinearModel {
+            u: Matrix::zero(f.dimx, 1),
+            f: f.clone(),
+            f_inv: f.checked_inverse().ok(),
+            q: q.clone(),
+            h: h.clone(),
+            r: r.clone(),
+            i: Matrix::identity(f.dimx),
+        }
+    }
+
+    // dx = a x dt + l dw with white noise dw of spectral density qc, sampled every dt
+    pub fn from_continuous(a: &Matrix<f32>, l: &Matrix<f32>, qc: &Matrix<f32>,
+            h: &Matrix<f32>, r: &Matrix<f32>, dt: f32) -> LinearModel {
+        let (f, q) = a.discretize(l, qc, dt);
+        let mut res = LinearModel::new(&f, &q, h, r);
+        // running the continuous model backwards gives inv(f) directly
+        let (f_inv, _) = (-*a).discretize(l, qc, dt);
+        res.f_inv = Some(f_inv);
+        res
+    }
+}
+
+
+// The estimate x and its covariance p.
+#[deriving(Show,Clone)]
+pub struct KalmanFilter {
+    pub x: Matrix<f32>,
+    pub p: Matrix<f32>,
+}
+
+
+impl KalmanFilter {
+    pub fn new(x: &Matrix<f32>, p: &Matrix<f32>) -> KalmanFilter {
+        KalmanFilter { x: x.clone(), p: p.clone() }
+    }
+
+    pub fn predict(&mut self, model: &LinearModel) {
+        self.x = model.f * self.x + model.u;                         // x := f * x + u
+        self.p = model.f * self.p * model.f.transpose() + model.q;   // p := f * p * trans(f) + q
+    }
+
+    // Folds in the column vector z; returns the innovation y and its covariance s.
+    pub fn update(&mut self, model: &LinearModel, z: &Matrix<f32>) -> (Matrix<f32>, Matrix<f32>) {
+        let h_t = model.h.transpose();
+        let y = *z - model.h * self.x;               // y = z - (h * x)
+        let s = model.h * self.p * h_t + model.r;    // s = h * p * trans(h) + r
+        let k = self.p * h_t * s.inverse();          // k = p * trans(h) * inv(s)
+        self.x = self.x + k * y;                     // x := x + (k * y)
+        self.p = (model.i - k * model.h) * self.p;   // p := (i - (k * h)) * p
+        (y, s)
+    }
+
+    // predict and update for each measurement in turn
+    pub fn run(&mut self, model: &LinearModel, measurements: &Vec<Vec<f32>>) {
+        for z in measurements.iter() {
+            self.predict(model);
+            self.update(model, &Matrix::new(vec![z.clone()]).unwrap().transpose());
+        }
+    }
+}
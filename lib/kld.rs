@@ -0,0 +1,67 @@
+use std::num::Float;
+use pose::Pose;
+use stats::chi2_quantile;
+
+
+// Settings for KLD-sampling (Fox 2003). Particles are drawn one at a time until there are
+// enough that, with probability 1 - delta, the KL divergence between the sample and the
+// true posterior, both histogrammed into the bins below, stays under epsilon. A spread out
+// cloud fills many bins and gets many particles; a converged one gets few.
+#[deriving(Show,Clone,PartialEq)]
+pub struct Kld {
+    pub xy_resolution: f32,    // bin side length in x and y
+    pub angle_resolution: f32, // bin width in orientation, in radians
+    pub epsilon: f64,          // error bound on the KL divergence
+    pub delta: f64,            // chance of exceeding it
+    pub min_particles: uint,
+    pub max_particles: uint,
+}
+
+
+impl Kld {
+    pub fn new(xy_resolution: f32, angle_resolution: f32) -> Kld {
+        Kld {
+            xy_resolution: xy_resolution,
+            angle_resolution: angle_resolution,
+            epsilon: 0.05,
+            delta: 0.01,
+            min_particles: 20,
+            max_particles: 5000,
+        }
+    }
+
+    pub fn with_bounds(mut self, epsilon: f64, delta: f64) -> Kld {
+        self.epsilon = epsilon;
+        self.delta = delta;
+        self
+    }
+
+    pub fn with_limits(mut self, min_particles: uint, max_particles: uint) -> Kld {
+        assert!(min_particles <= max_particles);
+        self.min_particles = min_particles;
+        self.max_particles = max_particles;
+        self
+    }
+
+    pub fn bin(&self, pose: &Pose) -> (i32, i32, i32) {
+        ((pose.x / self.xy_resolution).floor() as i32,
+         (pose.y / self.xy_resolution).floor() as i32,
+         (pose.orientation / self.angle_resolution).floor() as i32)
+    }
+
+    // Particles needed once they occupy k bins, clamped to the limits.
+    pub fn required(&self, k: uint) -> uint {
+        if k <= 1 {
+            return self.min_particles;
+        }
+        let n = (chi2_quantile(1.0 - self.delta, (k - 1) as f64) / (2.0 * self.epsilon)).ceil();
+        if n >= self.max_particles as f64 {
+            self.max_particles
+        } else if n <= self.min_particles as f64 {
+            self.min_particles
+        } else {
+            n as uint
+        }
+    }
+}
+
@@ -0,0 +1,28 @@
+#![crate_name = "cs373"]
+#![crate_type = "rlib"]
+#![feature(tuple_indexing)]
+
+pub mod matrix;
+pub mod lu;
+pub mod qr;
+pub mod sparse;
+pub mod eigen;
+pub mod svd;
+pub mod text;
+pub mod pose;
+pub mod particle;
+pub mod models;
+pub mod resample;
+pub mod kld;
+pub mod amcl;
+pub mod cluster;
+pub mod grid;
+pub mod rangefinder;
+pub mod seed;
+pub mod motion;
+pub mod association;
+pub mod regularize;
+pub mod metrics;
+pub mod stats;
+pub mod kalman;
+pub mod consistency;
@@ -0,0 +1,135 @@
+use std::num::{Float, zero, one, cast};
+use matrix::{Matrix, MatrixError, DimensionMismatch, Singular};
+
+
+// PA = LU, with partial pivoting. L (unit diagonal) and U share one matrix.
+#[deriving(Show,Clone)]
+pub struct Lu<T> {
+    lu: Matrix<T>,
+    perm: Vec<uint>,
+    sign: T,
+    singular: bool,
+}
+
+
+impl<T: Float> Lu<T> {
+    pub fn new(a: &Matrix<T>) -> Result<Lu<T>, MatrixError> {
+        if a.dimx != a.dimy {
+            return Err(DimensionMismatch((a.dimx, a.dimx), (a.dimx, a.dimy)));
+        }
+        let n = a.dimx;
+        let mut lu = a.clone();
+        let mut perm = Vec::from_fn(n, |i| i);
+        let mut sign: T = one();
+        let mut singular = false;
+
+        // pivots this small are round-off
+        let tol = a.norm_inf() * Float::epsilon() * cast(n).unwrap();
+
+        for k in range(0, n) {
+            let mut p = k;
+            for i in range(k+1, n) {
+                if lu[(i, k)].abs() > lu[(p, k)].abs() {
+                    p = i;
+                }
+            }
+            if p != k {
+                lu.swap_rows(p, k);
+                perm.as_mut_slice().swap(p, k);
+                sign = -sign;
+            }
+
+            let pivot = lu[(k, k)];
+            if pivot.abs() <= tol {
+                singular = true;
+                continue;
+            }
+            for i in range(k+1, n) {
+                let f = lu[(i, k)] / pivot;
+                lu[(i, k)] = f;
+                for j in range(k+1, n) {
+                    lu[(i, j)] = lu[(i, j)] - f * lu[(k, j)];
+                }
+            }
+        }
+
+        Ok(Lu {
+            lu: lu,
+            perm: perm,
+            sign: sign,
+            singular: singular,
+        })
+    }
+
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    pub fn determinant(&self) -> T {
+        if self.singular {
+            return zero();
+        }
+        let mut det = self.sign;
+        for k in range(0, self.lu.dimx) {
+            det = det * self.lu[(k, k)];
+        }
+        det
+    }
+
+    // x such that a * x = b, for each column of b
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let n = self.lu.dimx;
+        if b.dimx != n {
+            return Err(DimensionMismatch((n, b.dimy), (b.dimx, b.dimy)));
+        }
+        if self.singular {
+            return Err(Singular);
+        }
+
+        let mut x = Matrix::zero(n, b.dimy);
+        for c in range(0, b.dimy) {
+            // forward: l * y = p * b
+            for i in range(0, n) {
+                let mut s = b[(self.perm[i], c)];
+                for k in range(0, i) {
+                    s = s - self.lu[(i, k)] * x[(k, c)];
+                }
+                x[(i, c)] = s;
+            }
+            // backward: u * x = y
+            for i_ in range(0, n) {
+                let i = n - (i_ + 1);
+                let mut s = x[(i, c)];
+                for k in range(i+1, n) {
+                    s = s - self.lu[(i, k)] * x[(k, c)];
+                }
+                x[(i, c)] = s / self.lu[(i, i)];
+            }
+        }
+        Ok(x)
+    }
+
+    pub fn inverse(&self) -> Result<Matrix<T>, MatrixError> {
+        self.solve(&Matrix::identity(self.lu.dimx))
+    }
+}
+
+
+impl<T: Float> Matrix<T> {
+    pub fn lu(&self) -> Result<Lu<T>, MatrixError> {
+        Lu::new(self)
+    }
+
+    // x such that self * x = b; self needs to be square and non-singular
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.lu().and_then(|lu| lu.solve(b))
+    }
+
+    pub fn determinant(&self) -> Result<T, MatrixError> {
+        self.lu().map(|lu| lu.determinant())
+    }
+
+    pub fn checked_inverse(&self) -> Result<Matrix<T>, MatrixError> {
+        self.lu().and_then(|lu| lu.inverse())
+    }
+}
@@ -0,0 +1,745 @@
+use std::fmt;
+use std::fmt::Show;
+use std::cmp::{min, Ordering, Less, Equal, Greater};
+use std::iter::{range_step, range_step_inclusive, AdditiveIterator};
+use std::num::{Float, zero, one, cast};
+
+
+// Row-major in one buffer: element (i, j) is at data[i * dimy + j].
+#[deriving(Show,Clone,PartialEq)]
+pub struct Matrix<T> {
+    data: Vec<T>,
+    pub dimx: uint,
+    pub dimy: uint,
+}
+
+
+// Windows into a matrix's storage, from Matrix::view and Matrix::view_mut. Rows of the
+// window are `stride` (the matrix's dimy) apart.
+pub struct MatrixView<'a, T: 'a> {
+    data: &'a [T],
+    stride: uint,
+    pub dimx: uint,
+    pub dimy: uint,
+}
+
+
+pub struct MatrixViewMut<'a, T: 'a> {
+    data: &'a mut [T],
+    stride: uint,
+    pub dimx: uint,
+    pub dimy: uint,
+}
+
+
+// edge of the square tiles used by mul and transpose
+static BLOCK: uint = 64;
+
+
+#[deriving(Clone,PartialEq)]
+pub enum MatrixError {
+    DimensionMismatch((uint, uint), (uint, uint)), // expected, found
+    NotPositiveDefinite(uint),                     // row where the factorization broke down
+    Singular,
+    NotSymmetric,
+    IndexOutOfRange(uint, uint),                   // index, bound
+    ParseError(uint, String),                      // line, what was wrong
+}
+
+
+impl Show for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DimensionMismatch((ex, ey), (fx, fy)) =>
+                write!(f, "dimension mismatch: expected {}x{}, found {}x{}", ex, ey, fx, fy),
+            NotPositiveDefinite(i) =>
+                write!(f, "matrix not positive-definite (at row {})", i),
+            Singular =>
+                write!(f, "matrix is singular"),
+            NotSymmetric =>
+                write!(f, "matrix is not symmetric"),
+            IndexOutOfRange(i, n) =>
+                write!(f, "index {} out of range for dimension {}", i, n),
+            ParseError(line, ref msg) =>
+                write!(f, "parse error on line {}: {}", line, msg),
+        }
+    }
+}
+
+
+pub fn lit<T: Float>(x: f64) -> T {
+    cast(x).unwrap()
+}
+
+
+// the panicking operations fail with the error's message
+fn unwrap<T>(res: Result<T, MatrixError>) -> T {
+    match res {
+        Ok(x) => x,
+        Err(e) => fail!("{}", e),
+    }
+}
+
+
+fn check_indices(list: &Vec<uint>, bound: uint) -> Result<(), MatrixError> {
+    for &i in list.iter() {
+        if i >= bound {
+            return Err(IndexOutOfRange(i, bound));
+        }
+    }
+    Ok(())
+}
+
+
+pub fn dot<T: Float>(a: &[T], b: &[T]) -> T {
+    let mut s = zero();
+    for k in range(0, a.len()) {
+        s = s + a[k] * b[k];
+    }
+    s
+}
+
+
+
+// largest first with NaN after everything, a total order for sorting eigen and
+// singular values or weights
+pub fn descending<T: Float>(a: &T, b: &T) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Equal,
+        (true, false) => Greater,
+        (false, true) => Less,
+        (false, false) => b.partial_cmp(a).unwrap(),
+    }
+}
+
+// rows p, q <- c * row p - s * row q, s * row p + c * row q
+pub fn rotate_rows<T: Float>(a: &mut Matrix<T>, p: uint, q: uint, c: T, s: T) {
+    for k in range(0, a.dimy) {
+        let apk = a[(p, k)];
+        let aqk = a[(q, k)];
+        a[(p, k)] = c * apk - s * aqk;
+        a[(q, k)] = s * apk + c * aqk;
+    }
+}
+
+
+impl<T: Float> Matrix<T> {
+    // fails if the rows don't all have the same length
+    pub fn new(value: Vec<Vec<T>>) -> Result<Matrix<T>, MatrixError> {
+        let dimx = value.len();
+        let dimy = if dimx > 0 { value[0].len() } else { 0 };
+        let mut data = Vec::with_capacity(dimx * dimy);
+        for row in value.iter() {
+            if row.len() != dimy {
+                return Err(DimensionMismatch((1, dimy), (1, row.len())));
+            }
+            data.push_all(row.as_slice());
+        }
+        Ok(Matrix {
+            data: data,
+            dimx: dimx,
+            dimy: dimy,
+        })
+    }
+
+    // row-major elements
+    pub fn from_vec(dimx: uint, dimy: uint, data: Vec<T>) -> Result<Matrix<T>, MatrixError> {
+        if data.len() != dimx * dimy {
+            return Err(DimensionMismatch((dimx * dimy, 1), (data.len(), 1)));
+        }
+        Ok(Matrix {
+            data: data,
+            dimx: dimx,
+            dimy: dimy,
+        })
+    }
+
+    pub fn zero(dimx: uint, dimy: uint) -> Matrix<T> {
+        Matrix {
+            data: Vec::from_elem(dimx * dimy, zero()),
+            dimx: dimx,
+            dimy: dimy,
+        }
+    }
+
+    pub fn identity(dim: uint) -> Matrix<T> {
+        let mut res = Matrix::zero(dim, dim);
+        for i in range(0, dim) {
+            res[(i, i)] = one();
+        }
+        res
+    }
+
+    pub fn row(&self, i: uint) -> &[T] {
+        let start = i * self.dimy;
+        self.data.slice(start, start + self.dimy)
+    }
+
+    pub fn row_mut(&mut self, i: uint) -> &mut [T] {
+        let start = i * self.dimy;
+        let end = start + self.dimy;
+        self.data.slice_mut(start, end)
+    }
+
+    pub fn to_vec(&self) -> Vec<Vec<T>> {
+        Vec::from_fn(self.dimx, |i| self.row(i).to_vec())
+    }
+
+    // copied tile by tile so both sides stay in cache
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut res = Matrix::zero(self.dimy, self.dimx);
+        for ii in range_step(0, self.dimx, BLOCK) {
+            for jj in range_step(0, self.dimy, BLOCK) {
+                for i in range(ii, min(ii + BLOCK, self.dimx)) {
+                    for j in range(jj, min(jj + BLOCK, self.dimy)) {
+                        res[(j, i)] = self[(i, j)];
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    pub fn scale(&self, k: T) -> Matrix<T> {
+        let mut res = self.clone();
+        res.scale_mut(k);
+        res
+    }
+
+    pub fn scale_mut(&mut self, k: T) {
+        for x in self.data.iter_mut() {
+            *x = *x * k;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.data.iter().all(|&x| x == zero())
+    }
+
+    // max abs row sum
+    pub fn norm_inf(&self) -> T {
+        let mut norm = zero();
+        for i in range(0, self.dimx) {
+            let s = self.row(i).iter().map(|&x| x.abs()).sum();
+            if s > norm {
+                norm = s;
+            }
+        }
+        norm
+    }
+
+    pub fn max_abs_diff(&self, other: &Matrix<T>) -> T {
+        let d = *self - *other;
+        let mut res = zero();
+        for &x in d.data.iter() {
+            if x.abs() > res {
+                res = x.abs();
+            }
+        }
+        res
+    }
+
+    pub fn cholesky(&self, ztol: T) -> Matrix<T> {
+        unwrap(self.checked_cholesky(ztol))
+    }
+
+    // Upper triangular u with self = trans(u) * u. Built as its transpose, so the sums
+    // run along rows.
+    pub fn checked_cholesky(&self, ztol: T) -> Result<Matrix<T>, MatrixError> {
+        if self.dimx != self.dimy {
+            return Err(DimensionMismatch((self.dimx, self.dimx), (self.dimx, self.dimy)));
+        }
+        let mut l = Matrix::zero(self.dimx, self.dimx);
+        for i in range(0, self.dimx) {
+
+            let s = dot(l.row(i).slice_to(i), l.row(i).slice_to(i));
+
+            let d = self[(i, i)] - s;
+            l[(i, i)] = if d.abs() < ztol {
+                zero()
+            } else {
+                if d < zero() {
+                    return Err(NotPositiveDefinite(i));
+                }
+                d.sqrt()
+            };
+
+            for j in range(i+1, self.dimx) {
+                let mut s = dot(l.row(i).slice_to(i), l.row(j).slice_to(i));
+                if s.abs() < ztol {
+                    s = zero();
+                }
+
+                let v = self[(i, j)] - s;
+                l[(j, i)] = if l[(i, i)] != zero() {
+                    v / l[(i, i)]
+                } else if v.abs() < ztol {
+                    // a zero pivot is fine for a semidefinite self as long as the
+                    // rest of its column is zero too
+                    zero()
+                } else {
+                    return Err(NotPositiveDefinite(i));
+                };
+            }
+        }
+        Ok(l.transpose())
+    }
+
+    pub fn cholesky_inverse(&self) -> Matrix<T> {
+        let n = self.dimx;
+        let mut res = Matrix::zero(n, n);
+
+        // backward step for inverse. res is symmetric and the part of it read below is
+        // already filled in, so rows of res stand in for its columns.
+        for j_ in range_step_inclusive(n as int - 1, 0, -1i) {
+            let j = j_ as uint;
+            let tjj = self[(j, j)];
+
+            let s = dot(self.row(j).slice_from(j+1), res.row(j).slice_from(j+1));
+
+            // value[j][j] = 1 / tjj^2 - s / tjj
+            res[(j, j)] = (one::<T>() / (tjj * tjj)) - (s / tjj);
+
+            for i_ in range_step_inclusive(j_ - 1, 0, -1i) {
+                let i = i_ as uint;
+
+                let s = dot(self.row(i).slice_from(i+1), res.row(j).slice_from(i+1));
+                let v = -s / self[(i, i)];
+
+                res[(i, j)] = v;
+                res[(j, i)] = v;
+            }
+        }
+        res
+    }
+
+    // any non-singular matrix, through lu
+    pub fn inverse(&self) -> Matrix<T> {
+        unwrap(self.checked_inverse())
+    }
+
+    // symmetric positive definite matrices only, through cholesky
+    pub fn spd_inverse(&self) -> Matrix<T> {
+        unwrap(self.checked_spd_inverse())
+    }
+
+    pub fn checked_spd_inverse(&self) -> Result<Matrix<T>, MatrixError> {
+        let aux = try!(self.checked_cholesky(lit(0.00001)));
+        // a semidefinite self still factors, with a zero pivot, but has no inverse
+        for j in range(0, aux.dimx) {
+            if aux[(j, j)] == zero() {
+                return Err(NotPositiveDefinite(j));
+            }
+        }
+        Ok(aux.cholesky_inverse())
+    }
+
+    // gathers rows list1 and columns list2 (or list1 again when list2 is empty) of self
+    pub fn take(&self, list1: &Vec<uint>, list2: &Vec<uint>) -> Matrix<T> {
+        unwrap(self.checked_take(list1, list2))
+    }
+
+    pub fn checked_take(&self, list1: &Vec<uint>, list2: &Vec<uint>) -> Result<Matrix<T>, MatrixError> {
+        let list2 = if list2.len() == 0 { list1.clone() } else { list2.clone() };
+
+        try!(check_indices(list1, self.dimx));
+        try!(check_indices(&list2, self.dimy));
+
+        let mut res = Matrix::zero(list1.len(), list2.len());
+        for i in range(0u, list1.len()) {
+            for j in range(0u, list2.len()) {
+                res[(i, j)] = self[(list1[i], list2[j])];
+            }
+        }
+        Ok(res)
+    }
+
+    // scatters self into a zero dimx x dimy matrix: row i goes to v1[i], column j to v2[j]
+    // (or v1[j] when v2 is empty, as in take)
+    pub fn expand(&self, dimx: uint, dimy: uint, v1: &Vec<uint>, v2: &Vec<uint>) -> Matrix<T> {
+        unwrap(self.checked_expand(dimx, dimy, v1, v2))
+    }
+
+    pub fn checked_expand(&self, dimx: uint, dimy: uint,
+            v1: &Vec<uint>, v2: &Vec<uint>) -> Result<Matrix<T>, MatrixError> {
+        let v2 = if v2.len() == 0 { v1.clone() } else { v2.clone() };
+        let mut res = Matrix::zero(dimx, dimy);
+        try!(res.checked_add_at(v1, &v2, self));
+        Ok(res)
+    }
+
+    // self[rows[i]][cols[j]] += other[i][j], e.g. to add a factor to an information matrix
+    pub fn add_at(&mut self, rows: &Vec<uint>, cols: &Vec<uint>, other: &Matrix<T>) {
+        unwrap(self.checked_add_at(rows, cols, other))
+    }
+
+    pub fn checked_add_at(&mut self, rows: &Vec<uint>, cols: &Vec<uint>,
+            other: &Matrix<T>) -> Result<(), MatrixError> {
+        if rows.len() != other.dimx || cols.len() != other.dimy {
+            return Err(DimensionMismatch((other.dimx, other.dimy), (rows.len(), cols.len())));
+        }
+        try!(check_indices(rows, self.dimx));
+        try!(check_indices(cols, self.dimy));
+
+        for i in range(0u, rows.len()) {
+            for j in range(0u, cols.len()) {
+                self[(rows[i], cols[j])] = self[(rows[i], cols[j])] + other[(i, j)];
+            }
+        }
+        Ok(())
+    }
+
+    // Marginalizes the variables `drop` out of the information form (self, xi) through
+    // the schur complement of self[drop][drop]. Returns the omega and xi of the variables
+    // that remain, in their original order.
+    pub fn marginalize(&self, xi: &Matrix<T>,
+            drop: &Vec<uint>) -> Result<(Matrix<T>, Matrix<T>), MatrixError> {
+        if self.dimx != self.dimy {
+            return Err(DimensionMismatch((self.dimx, self.dimx), (self.dimx, self.dimy)));
+        }
+        if xi.dimx != self.dimx {
+            return Err(DimensionMismatch((self.dimx, xi.dimy), (xi.dimx, xi.dimy)));
+        }
+        try!(check_indices(drop, self.dimx));
+        let keep: Vec<uint> = range(0, self.dimx).filter(|i| !drop.contains(i)).collect();
+        let xi_cols = Vec::from_fn(xi.dimy, |j| j);
+
+        let a = try!(self.checked_take(drop, &keep));
+        let b = try!(self.checked_take(drop, &vec![]));
+        let c = try!(xi.checked_take(drop, &xi_cols));
+
+        let a_t = a.transpose();
+        let mut omega = try!(self.checked_take(&keep, &vec![]));
+        try!(omega.checked_sub_assign(&(a_t * try!(b.solve(&a)))));
+        let mut xi = try!(xi.checked_take(&keep, &xi_cols));
+        try!(xi.checked_sub_assign(&(a_t * try!(b.solve(&c)))));
+        Ok((omega, xi))
+    }
+
+    pub fn checked_add(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let mut res = self.clone();
+        try!(res.checked_add_assign(other));
+        Ok(res)
+    }
+
+    pub fn checked_sub(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let mut res = self.clone();
+        try!(res.checked_sub_assign(other));
+        Ok(res)
+    }
+
+    pub fn add_assign(&mut self, other: &Matrix<T>) {
+        unwrap(self.checked_add_assign(other))
+    }
+
+    pub fn sub_assign(&mut self, other: &Matrix<T>) {
+        unwrap(self.checked_sub_assign(other))
+    }
+
+    pub fn checked_add_assign(&mut self, other: &Matrix<T>) -> Result<(), MatrixError> {
+        if self.dimx != other.dimx || self.dimy != other.dimy {
+            return Err(DimensionMismatch((self.dimx, self.dimy), (other.dimx, other.dimy)));
+        }
+        for i in range(0, self.dimx) {
+            let src = other.row(i);
+            let dst = self.row_mut(i);
+            for j in range(0, dst.len()) {
+                dst[j] = dst[j] + src[j];
+            }
+        }
+        Ok(())
+    }
+
+    pub fn checked_sub_assign(&mut self, other: &Matrix<T>) -> Result<(), MatrixError> {
+        if self.dimx != other.dimx || self.dimy != other.dimy {
+            return Err(DimensionMismatch((self.dimx, self.dimy), (other.dimx, other.dimy)));
+        }
+        for i in range(0, self.dimx) {
+            let src = other.row(i);
+            let dst = self.row_mut(i);
+            for j in range(0, dst.len()) {
+                dst[j] = dst[j] - src[j];
+            }
+        }
+        Ok(())
+    }
+
+    pub fn checked_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let mut res = Matrix::zero(self.dimx, other.dimy);
+        try!(self.mul_into(other, &mut res));
+        Ok(res)
+    }
+
+    // res = self * other, reusing res's buffer. Tiled so a block of other is reused
+    // across many rows of self while it is still in cache.
+    pub fn mul_into(&self, other: &Matrix<T>, res: &mut Matrix<T>) -> Result<(), MatrixError> {
+        if self.dimy != other.dimx {
+            return Err(DimensionMismatch((self.dimy, other.dimy), (other.dimx, other.dimy)));
+        }
+        if res.dimx != self.dimx || res.dimy != other.dimy {
+            return Err(DimensionMismatch((self.dimx, other.dimy), (res.dimx, res.dimy)));
+        }
+        for x in res.data.iter_mut() {
+            *x = zero();
+        }
+        for ii in range_step(0, self.dimx, BLOCK) {
+            for kk in range_step(0, self.dimy, BLOCK) {
+                for jj in range_step(0, other.dimy, BLOCK) {
+                    let j_end = min(jj + BLOCK, other.dimy);
+                    for i in range(ii, min(ii + BLOCK, self.dimx)) {
+                        for k in range(kk, min(kk + BLOCK, self.dimy)) {
+                            let a = self[(i, k)];
+                            if a == zero() {
+                                continue;
+                            }
+                            let src = other.row(k).slice(jj, j_end);
+                            let dst = res.row_mut(i).slice_mut(jj, j_end);
+                            for j in range(0, src.len()) {
+                                dst[j] = dst[j] + a * src[j];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn swap_rows(&mut self, a: uint, b: uint) {
+        for j in range(0, self.dimy) {
+            self.data.as_mut_slice().swap(a * self.dimy + j, b * self.dimy + j);
+        }
+    }
+
+    pub fn swap_cols(&mut self, a: uint, b: uint) {
+        for i in range(0, self.dimx) {
+            self.row_mut(i).swap(a, b);
+        }
+    }
+
+    // a dimx x dimy window of self whose top left is (row, col), without copying
+    pub fn view<'a>(&'a self, row: uint, col: uint, dimx: uint, dimy: uint) -> MatrixView<'a, T> {
+        unwrap(self.checked_view(row, col, dimx, dimy))
+    }
+
+    pub fn checked_view<'a>(&'a self, row: uint, col: uint,
+            dimx: uint, dimy: uint) -> Result<MatrixView<'a, T>, MatrixError> {
+        try!(self.check_window(row, col, dimx, dimy));
+        Ok(MatrixView {
+            data: self.data.slice_from(row * self.dimy + col),
+            stride: self.dimy,
+            dimx: dimx,
+            dimy: dimy,
+        })
+    }
+
+    pub fn view_mut<'a>(&'a mut self, row: uint, col: uint,
+            dimx: uint, dimy: uint) -> MatrixViewMut<'a, T> {
+        unwrap(self.checked_view_mut(row, col, dimx, dimy))
+    }
+
+    pub fn checked_view_mut<'a>(&'a mut self, row: uint, col: uint,
+            dimx: uint, dimy: uint) -> Result<MatrixViewMut<'a, T>, MatrixError> {
+        try!(self.check_window(row, col, dimx, dimy));
+        let stride = self.dimy;
+        Ok(MatrixViewMut {
+            data: self.data.slice_from_mut(row * stride + col),
+            stride: stride,
+            dimx: dimx,
+            dimy: dimy,
+        })
+    }
+
+    // the last row and column of the window, if it has any, have to be inside self
+    fn check_window(&self, row: uint, col: uint, dimx: uint, dimy: uint) -> Result<(), MatrixError> {
+        if row + dimx > self.dimx {
+            return Err(IndexOutOfRange(row + dimx - 1, self.dimx));
+        }
+        if col + dimy > self.dimy {
+            return Err(IndexOutOfRange(col + dimy - 1, self.dimy));
+        }
+        Ok(())
+    }
+
+    pub fn block(&self, row: uint, col: uint, dimx: uint, dimy: uint) -> Matrix<T> {
+        self.view(row, col, dimx, dimy).to_matrix()
+    }
+
+    pub fn set_block(&mut self, row: uint, col: uint, other: &Matrix<T>) {
+        self.view_mut(row, col, other.dimx, other.dimy).copy_from(other);
+    }
+
+    // e^self, by scaling and squaring a truncated taylor series
+    pub fn expm(&self) -> Matrix<T> {
+        assert!(self.dimx == self.dimy);
+
+        // scale down until the series converges quickly
+        let norm = self.norm_inf();
+        assert!(norm.is_finite(), "expm of a matrix with non-finite entries");
+        let mut squarings = 0u;
+        let mut k: T = one();
+        while norm * k > lit(0.5) {
+            k = k / lit(2.0);
+            squarings += 1;
+        }
+        let a = self.scale(k);
+
+        let mut res = Matrix::identity(self.dimx);
+        let mut term = Matrix::identity(self.dimx);
+        for n in range(1u, 20u) {
+            term = (term * a).scale(one::<T>() / cast(n).unwrap());
+            res = res + term;
+            if term.norm_inf() <= Float::epsilon() {
+                break;
+            }
+        }
+
+        for _ in range(0, squarings) {
+            res = res * res;
+        }
+        res
+    }
+
+    // Van Loan's method. `self` is the continuous time system a in dx = a x dt + l dw,
+    // where the white noise dw has spectral density qc. Returns the (f, q) of the
+    // equivalent discrete time model over a step of dt.
+    pub fn discretize(&self, l: &Matrix<T>, qc: &Matrix<T>, dt: T) -> (Matrix<T>, Matrix<T>) {
+        assert!(self.dimx == self.dimy);
+        assert!(l.dimx == self.dimx);
+        let n = self.dimx;
+
+        // m = [ -a  l qc trans(l) ]
+        //     [  0      trans(a)  ]
+        let mut m = Matrix::zero(2 * n, 2 * n);
+        m.set_block(0, 0, &-*self);
+        m.set_block(0, n, &(*l * *qc * l.transpose()));
+        m.set_block(n, n, &self.transpose());
+
+        // e^(m dt) = [ ...  inv(f) q ]
+        //            [  0   trans(f) ]
+        let e = m.scale(dt).expm();
+        let f = e.block(n, n, n, n).transpose();
+        let q = f * e.block(0, n, n, n);
+        (f, q)
+    }
+}
+
+
+impl<T> Index<(uint, uint), T> for Matrix<T> {
+    fn index<'a>(&'a self, &(i, j): &(uint, uint)) -> &'a T {
+        assert!(i < self.dimx && j < self.dimy);
+        &self.data[i * self.dimy + j]
+    }
+}
+
+
+impl<T> IndexMut<(uint, uint), T> for Matrix<T> {
+    fn index_mut<'a>(&'a mut self, &(i, j): &(uint, uint)) -> &'a mut T {
+        assert!(i < self.dimx && j < self.dimy);
+        self.data.get_mut(i * self.dimy + j)
+    }
+}
+
+
+
+impl<'a, T: Float> MatrixView<'a, T> {
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut res = Matrix::zero(self.dimx, self.dimy);
+        for i in range(0, self.dimx) {
+            for j in range(0, self.dimy) {
+                res[(i, j)] = self[(i, j)];
+            }
+        }
+        res
+    }
+}
+
+
+impl<'a, T: Float> MatrixViewMut<'a, T> {
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut res = Matrix::zero(self.dimx, self.dimy);
+        for i in range(0, self.dimx) {
+            for j in range(0, self.dimy) {
+                res[(i, j)] = self[(i, j)];
+            }
+        }
+        res
+    }
+
+    pub fn copy_from(&mut self, other: &Matrix<T>) {
+        assert!(self.dimx == other.dimx && self.dimy == other.dimy);
+        for i in range(0, self.dimx) {
+            for j in range(0, self.dimy) {
+                self[(i, j)] = other[(i, j)];
+            }
+        }
+    }
+
+    pub fn add_assign(&mut self, other: &Matrix<T>) {
+        unwrap(self.checked_add_assign(other))
+    }
+
+    pub fn checked_add_assign(&mut self, other: &Matrix<T>) -> Result<(), MatrixError> {
+        if self.dimx != other.dimx || self.dimy != other.dimy {
+            return Err(DimensionMismatch((self.dimx, self.dimy), (other.dimx, other.dimy)));
+        }
+        for i in range(0, self.dimx) {
+            for j in range(0, self.dimy) {
+                self[(i, j)] = self[(i, j)] + other[(i, j)];
+            }
+        }
+        Ok(())
+    }
+}
+
+
+impl<'a, T> Index<(uint, uint), T> for MatrixView<'a, T> {
+    fn index<'b>(&'b self, &(i, j): &(uint, uint)) -> &'b T {
+        assert!(i < self.dimx && j < self.dimy);
+        &self.data[i * self.stride + j]
+    }
+}
+
+
+impl<'a, T> Index<(uint, uint), T> for MatrixViewMut<'a, T> {
+    fn index<'b>(&'b self, &(i, j): &(uint, uint)) -> &'b T {
+        assert!(i < self.dimx && j < self.dimy);
+        &self.data[i * self.stride + j]
+    }
+}
+
+
+impl<'a, T> IndexMut<(uint, uint), T> for MatrixViewMut<'a, T> {
+    fn index_mut<'b>(&'b mut self, &(i, j): &(uint, uint)) -> &'b mut T {
+        assert!(i < self.dimx && j < self.dimy);
+        &mut self.data[i * self.stride + j]
+    }
+}
+
+
+impl<T: Float> Add<Matrix<T>, Matrix<T>> for Matrix<T> {
+    fn add(&self, other: &Matrix<T>) -> Matrix<T> {
+        unwrap(self.checked_add(other))
+    }
+}
+
+
+impl<T: Float> Sub<Matrix<T>, Matrix<T>> for Matrix<T> {
+    fn sub(&self, other: &Matrix<T>) -> Matrix<T> {
+        unwrap(self.checked_sub(other))
+    }
+}
+
+
+impl<T: Float> Mul<Matrix<T>, Matrix<T>> for Matrix<T> {
+    fn mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        unwrap(self.checked_mul(other))
+    }
+}
+
+
+impl<T: Float> Neg<Matrix<T>> for Matrix<T> {
+    fn neg(&self) -> Matrix<T> {
+        self.scale(-one::<T>())
+    }
+}
@@ -0,0 +1,191 @@
+use std::num::Float;
+use pose::{Pose, HasPose, modulo, angle_diff};
+
+
+// Per-step errors of one localization run: how far the estimate was from the truth, and
+// how far off its heading was, in radians.
+#[deriving(Show,Clone,PartialEq)]
+pub struct Run {
+    pub position: Vec<f32>,
+    pub orientation: Vec<f32>,
+}
+
+
+impl Run {
+    pub fn new() -> Run {
+        Run { position: Vec::new(), orientation: Vec::new() }
+    }
+
+    // errors as returned by Evaluator::error and particle_error
+    pub fn record(&mut self, (position, orientation): (f32, f32)) {
+        self.position.push(position);
+        self.orientation.push(orientation.abs());
+    }
+
+    pub fn len(&self) -> uint {
+        self.position.len()
+    }
+
+    pub fn position_rmse(&self) -> f64 {
+        rms(&self.position)
+    }
+
+    pub fn orientation_rmse(&self) -> f64 {
+        rms(&self.orientation)
+    }
+}
+
+
+// Scores estimates against the truth. Positions can wrap around a square torus world,
+// as in unit 3, where a robot leaving on one side comes back on the other. A run has
+// converged at the first step from which both errors stay within the tolerances.
+#[deriving(Show,Clone,PartialEq)]
+pub struct Evaluator {
+    pub xy_tolerance: f32,
+    pub orientation_tolerance: f32,
+    pub world_size: Option<f32>, // side of the torus, if the world wraps
+}
+
+
+impl Evaluator {
+    pub fn new(xy_tolerance: f32, orientation_tolerance: f32) -> Evaluator {
+        Evaluator {
+            xy_tolerance: xy_tolerance,
+            orientation_tolerance: orientation_tolerance,
+            world_size: None,
+        }
+    }
+
+    pub fn with_world_size(mut self, world_size: f32) -> Evaluator {
+        self.world_size = Some(world_size);
+        self
+    }
+
+    // (distance, signed heading difference) from truth to estimate
+    pub fn error(&self, truth: &Pose, estimate: &Pose) -> (f32, f32) {
+        let (dx, dy) = match self.world_size {
+            Some(size) => (wrap(estimate.x - truth.x, size), wrap(estimate.y - truth.y, size)),
+            None => (estimate.x - truth.x, estimate.y - truth.y),
+        };
+        ((dx * dx + dy * dy).sqrt(), angle_diff(estimate.orientation, truth.orientation))
+    }
+
+    // The weighted mean distance of the particles from the truth, and likewise the mean
+    // absolute heading difference; how spread out the cloud is as well as where.
+    pub fn particle_error<S: HasPose>(&self, truth: &Pose, particles: &Vec<S>,
+            weights: &Vec<f64>) -> (f32, f32) {
+        assert!(particles.len() == weights.len());
+        let (mut position, mut orientation) = (0.0, 0.0);
+        for (p, &w) in particles.iter().zip(weights.iter()) {
+            let (d, a) = self.error(truth, &p.pose());
+            position += w * d as f64;
+            orientation += w * a.abs() as f64;
+        }
+        (position as f32, orientation as f32)
+    }
+
+    pub fn within_tolerance(&self, position: f32, orientation: f32) -> bool {
+        position <= self.xy_tolerance && orientation.abs() <= self.orientation_tolerance
+    }
+
+    // the first step from which the run stays within the tolerances, if there is one
+    pub fn converged_at(&self, run: &Run) -> Option<uint> {
+        let mut res = None;
+        for i in range(0, run.len()) {
+            if !self.within_tolerance(run.position[i], run.orientation[i]) {
+                res = None;
+            } else if res.is_none() {
+                res = Some(i);
+            }
+        }
+        res
+    }
+
+    pub fn summarize(&self, runs: &Vec<Run>) -> Summary {
+        let mut position = Vec::new();
+        let mut orientation = Vec::new();
+        for run in runs.iter() {
+            position.push_all(run.position.as_slice());
+            orientation.push_all(run.orientation.as_slice());
+        }
+        let times: Vec<uint> = runs.iter().filter_map(|r| self.converged_at(r)).collect();
+        Summary {
+            runs: runs.len(),
+            steps: position.len(),
+            position_rmse: rms(&position),
+            orientation_rmse: rms(&orientation),
+            mean_position: mean(&position),
+            max_position: max(&position),
+            mean_orientation: mean(&orientation),
+            max_orientation: max(&orientation),
+            converged: times.len() as f64 / runs.len() as f64,
+            mean_convergence_step: if times.len() > 0 {
+                Some(times.iter().fold(0.0, |a, &t| a + t as f64) / times.len() as f64)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+
+// Errors over every step of a batch of Monte Carlo runs. Convergence steps are counted
+// from 0 and averaged over the runs that converged.
+#[deriving(Show,Clone,PartialEq)]
+pub struct Summary {
+    pub runs: uint,
+    pub steps: uint,
+    pub position_rmse: f64,
+    pub orientation_rmse: f64,
+    pub mean_position: f64,
+    pub max_position: f64,
+    pub mean_orientation: f64,
+    pub max_orientation: f64,
+    pub converged: f64, // fraction of the runs
+    pub mean_convergence_step: Option<f64>,
+}
+
+
+impl Summary {
+    // one line of JSON, with null for anything undefined
+    pub fn to_json(&self) -> String {
+        format!("{{\"runs\": {}, \"steps\": {}, \"position_rmse\": {}, \
+                 \"orientation_rmse\": {}, \"mean_position\": {}, \"max_position\": {}, \
+                 \"mean_orientation\": {}, \"max_orientation\": {}, \"converged\": {}, \
+                 \"mean_convergence_step\": {}}}",
+                self.runs, self.steps, json(Some(self.position_rmse)),
+                json(Some(self.orientation_rmse)), json(Some(self.mean_position)),
+                json(Some(self.max_position)), json(Some(self.mean_orientation)),
+                json(Some(self.max_orientation)), json(Some(self.converged)),
+                json(self.mean_convergence_step))
+    }
+}
+
+
+// d wrapped into [-size / 2, size / 2)
+fn wrap(d: f32, size: f32) -> f32 {
+    modulo(d + size / 2.0, size) - size / 2.0
+}
+
+
+fn rms(xs: &Vec<f32>) -> f64 {
+    (xs.iter().fold(0.0, |a, &x| a + (x as f64).powi(2)) / xs.len() as f64).sqrt()
+}
+
+
+fn mean(xs: &Vec<f32>) -> f64 {
+    xs.iter().fold(0.0, |a, &x| a + x as f64) / xs.len() as f64
+}
+
+
+fn max(xs: &Vec<f32>) -> f64 {
+    xs.iter().fold(Float::nan(), |a: f64, &x| if a.is_nan() || x as f64 > a { x as f64 } else { a })
+}
+
+
+fn json(x: Option<f64>) -> String {
+    match x {
+        Some(x) if x.is_finite() => format!("{}", x),
+        _ => String::from_str("null"),
+    }
+}
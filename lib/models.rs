@@ -0,0 +1,280 @@
+use std::num::{Float, FloatMath};
+use std::rand::Rng;
+use std::rand::distributions::{Normal, IndependentSample};
+use particle::{MeasurementModel, log_sum_exp};
+use pose::{HasPose, angle_diff};
+
+
+// density of N(mu, sigma^2) at x
+pub fn gaussian(mu: f64, sigma: f64, x: f64) -> f64 {
+    log_gaussian(mu, sigma, x).exp()
+}
+
+
+// log of the density, which stays finite far out in the tails where `gaussian` is 0
+pub fn log_gaussian(mu: f64, sigma: f64, x: f64) -> f64 {
+    let two_pi: f64 = Float::two_pi();
+    -(x - mu).powi(2) / (2.0 * sigma * sigma) - 0.5 * (two_pi * sigma * sigma).ln()
+}
+
+
+// zero-mean gaussian noise with std dev sigma; exactly 0 when sigma is 0 or less
+pub fn gauss<R: Rng>(sigma: f64, rng: &mut R) -> f64 {
+    if sigma <= 0.0 {
+        return 0.0;
+    }
+    Normal::new(0.0, sigma).ind_sample(rng)
+}
+
+
+// Distance to each landmark, in order, with gaussian noise of std dev `noise`.
+#[deriving(Show,Clone)]
+pub struct RangeModel {
+    pub landmarks: Vec<(f32, f32)>,
+    pub noise: f32,
+}
+
+
+impl RangeModel {
+    pub fn new(landmarks: Vec<(f32, f32)>, noise: f32) -> RangeModel {
+        RangeModel { landmarks: landmarks, noise: noise }
+    }
+
+    pub fn expected<S: HasPose>(&self, state: &S) -> Vec<f32> {
+        let pose = state.pose();
+        self.landmarks.iter().map(|&l| pose.distance_to(l)).collect()
+    }
+}
+
+
+impl<S: HasPose> MeasurementModel<S, Vec<f32>> for RangeModel {
+    fn log_likelihood(&self, state: &S, z: &Vec<f32>) -> f64 {
+        assert!(z.len() == self.landmarks.len());
+        let mut res = 0.0;
+        for (&dist, &zi) in self.expected(state).iter().zip(z.iter()) {
+            res += log_gaussian(dist as f64, self.noise as f64, zi as f64);
+        }
+        res
+    }
+}
+
+
+// Bearing to each landmark relative to the heading, in order, with gaussian noise.
+#[deriving(Show,Clone)]
+pub struct BearingModel {
+    pub landmarks: Vec<(f32, f32)>,
+    pub noise: f32,
+}
+
+
+impl BearingModel {
+    pub fn new(landmarks: Vec<(f32, f32)>, noise: f32) -> BearingModel {
+        BearingModel { landmarks: landmarks, noise: noise }
+    }
+
+    pub fn expected<S: HasPose>(&self, state: &S) -> Vec<f32> {
+        let pose = state.pose();
+        self.landmarks.iter().map(|&l| pose.bearing_to(l)).collect()
+    }
+}
+
+
+impl<S: HasPose> MeasurementModel<S, Vec<f32>> for BearingModel {
+    fn log_likelihood(&self, state: &S, z: &Vec<f32>) -> f64 {
+        assert!(z.len() == self.landmarks.len());
+        let mut res = 0.0;
+        for (&bearing, &zi) in self.expected(state).iter().zip(z.iter()) {
+            res += log_gaussian(0.0, self.noise as f64, angle_diff(zi, bearing) as f64);
+        }
+        res
+    }
+}
+
+
+// A noisy (x, y) fix, like gps.
+#[deriving(Show,Clone)]
+pub struct GpsModel {
+    pub noise: f32,
+}
+
+
+impl GpsModel {
+    pub fn new(noise: f32) -> GpsModel {
+        GpsModel { noise: noise }
+    }
+}
+
+
+impl<S: HasPose> MeasurementModel<S, (f32, f32)> for GpsModel {
+    fn log_likelihood(&self, state: &S, &(x, y): &(f32, f32)) -> f64 {
+        let pose = state.pose();
+        let sigma = self.noise as f64;
+        log_gaussian(pose.x as f64, sigma, x as f64) + log_gaussian(pose.y as f64, sigma, y as f64)
+    }
+}
+
+
+// One landmark sighting. `landmark` is the index of the landmark it came from, or None
+// when that is not known, as for clutter.
+#[deriving(Show,Clone,PartialEq)]
+pub struct Detection {
+    pub range: f32,
+    pub bearing: f32, // relative to the heading, in [-pi, pi)
+    pub landmark: Option<uint>,
+}
+
+
+// Range and bearing to landmarks, as a real sensor sees them: only those within max_range
+// and fov (centred on the heading), each missed with probability 1 - p_detect, plus on
+// average clutter_rate spurious detections spread evenly over the field of view. Clutter
+// needs a field of view of finite area, so with an unbounded max_range there is none. A
+// scan comes in no particular order.
+#[deriving(Show,Clone)]
+pub struct RangeBearingModel {
+    pub landmarks: Vec<(f32, f32)>,
+    pub range_noise: f32,
+    pub bearing_noise: f32,
+    pub max_range: f32,
+    pub fov: f32,
+    pub p_detect: f32,
+    pub clutter_rate: f32,
+}
+
+
+impl RangeBearingModel {
+    // sees everything, every time, with no clutter
+    pub fn new(landmarks: Vec<(f32, f32)>, range_noise: f32, bearing_noise: f32) -> RangeBearingModel {
+        RangeBearingModel {
+            landmarks: landmarks,
+            range_noise: range_noise,
+            bearing_noise: bearing_noise,
+            max_range: Float::infinity(),
+            fov: Float::two_pi(),
+            p_detect: 1.0,
+            clutter_rate: 0.0,
+        }
+    }
+
+    pub fn with_limits(mut self, max_range: f32, fov: f32) -> RangeBearingModel {
+        self.max_range = max_range;
+        self.fov = fov;
+        self
+    }
+
+    pub fn with_detection(mut self, p_detect: f32, clutter_rate: f32) -> RangeBearingModel {
+        self.p_detect = p_detect;
+        self.clutter_rate = clutter_rate;
+        self
+    }
+
+    // noiseless (range, bearing) to landmark i, if the sensor can see it from state
+    pub fn expected<S: HasPose>(&self, state: &S, i: uint) -> Option<(f32, f32)> {
+        let pose = state.pose();
+        let l = self.landmarks[i];
+        let range = pose.distance_to(l);
+        let bearing = angle_diff(pose.bearing_to(l), 0.0);
+        if range <= self.max_range && bearing.abs() <= self.fov / 2.0 {
+            Some((range, bearing))
+        } else {
+            None
+        }
+    }
+
+    // a simulated scan from state
+    pub fn sense<S: HasPose, R: Rng>(&self, state: &S, rng: &mut R) -> Vec<Detection> {
+        let range_noise = Normal::new(0.0, self.range_noise as f64);
+        let bearing_noise = Normal::new(0.0, self.bearing_noise as f64);
+        let mut z = Vec::new();
+        for i in range(0, self.landmarks.len()) {
+            match self.expected(state, i) {
+                Some((r, b)) if rng.gen::<f32>() < self.p_detect => z.push(Detection {
+                    range: r + range_noise.ind_sample(rng) as f32,
+                    bearing: angle_diff(b + bearing_noise.ind_sample(rng) as f32, 0.0),
+                    landmark: Some(i),
+                }),
+                _ => {}
+            }
+        }
+        // none for an unbounded range, where clutter_density is 0 too
+        let clutter = if self.max_range.is_finite() {
+            poisson(self.clutter_rate as f64, rng)
+        } else {
+            0
+        };
+        for _ in range(0, clutter) {
+            z.push(Detection {
+                range: rng.gen::<f32>() * self.max_range,
+                bearing: (rng.gen::<f32>() - 0.5) * self.fov,
+                landmark: None,
+            });
+        }
+        rng.shuffle(z.as_mut_slice());
+        z
+    }
+
+    // density of a clutter detection over the (range, bearing) field of view
+    pub fn clutter_density(&self) -> f64 {
+        if self.clutter_rate <= 0.0 || !self.max_range.is_finite() {
+            return 0.0;
+        }
+        self.clutter_rate as f64 / (self.max_range as f64 * self.fov as f64)
+    }
+
+    // log density of detection z coming from landmark i as seen from state
+    pub fn log_detection<S: HasPose>(&self, state: &S, z: &Detection, i: uint) -> f64 {
+        (self.p_detect as f64).ln() + self.log_sighting(state, z, i)
+    }
+
+    // the same, given that landmark i was detected at all
+    pub fn log_sighting<S: HasPose>(&self, state: &S, z: &Detection, i: uint) -> f64 {
+        match self.expected(state, i) {
+            Some((r, b)) =>
+                log_gaussian(r as f64, self.range_noise as f64, z.range as f64) +
+                log_gaussian(0.0, self.bearing_noise as f64, angle_diff(z.bearing, b) as f64),
+            None => Float::neg_infinity(),
+        }
+    }
+}
+
+
+// Detections carrying a landmark index may come in any order and need not cover every
+// landmark. Each is either a sighting of its landmark or clutter; unlabelled detections,
+// and those labelled with a landmark the model does not know, can only be clutter. Every
+// landmark the state could see but that was not reported counts as a miss.
+impl<S: HasPose> MeasurementModel<S, Vec<Detection>> for RangeBearingModel {
+    fn log_likelihood(&self, state: &S, z: &Vec<Detection>) -> f64 {
+        let clutter = self.clutter_density();
+        let mut seen = Vec::from_elem(self.landmarks.len(), false);
+        let mut res = 0.0;
+        for d in z.iter() {
+            res += match d.landmark {
+                Some(i) if i < self.landmarks.len() => {
+                    *seen.get_mut(i) = true;
+                    // in the log domain, so a sighting far in the tail stays finite
+                    log_sum_exp(&vec![self.log_detection(state, d, i), clutter.ln()])
+                }
+                _ => clutter.ln(),
+            };
+        }
+        let miss = (1.0 - self.p_detect as f64).ln();
+        for i in range(0, self.landmarks.len()) {
+            if !seen[i] && self.expected(state, i).is_some() {
+                res += miss;
+            }
+        }
+        res
+    }
+}
+
+
+// number of events in a unit interval of a Poisson process with the given rate
+fn poisson<R: Rng>(rate: f64, rng: &mut R) -> uint {
+    let limit = (-rate).exp();
+    let mut k = 0;
+    let mut p = rng.gen::<f64>();
+    while p > limit {
+        k += 1;
+        p *= rng.gen::<f64>();
+    }
+    k
+}
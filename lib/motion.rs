@@ -0,0 +1,187 @@
+use std::num::{Float, FloatMath};
+use std::rand::Rng;
+use matrix::Matrix;
+use particle::MotionModel;
+use pose::{Pose, angle_diff};
+use models::gauss;
+
+
+// A motion model an EKF can predict with: the noise-free motion, its Jacobians with
+// respect to the state (x, y, orientation) and to the control, and the covariance of the
+// control noise.
+pub trait LinearizedMotion<U> {
+    fn mean(&self, pose: &Pose, control: &U) -> Pose;
+    fn state_jacobian(&self, pose: &Pose, control: &U) -> Matrix<f64>;
+    fn control_jacobian(&self, pose: &Pose, control: &U) -> Matrix<f64>;
+    fn control_noise(&self, pose: &Pose, control: &U) -> Matrix<f64>;
+
+    // EKF prediction: the moved mean and G sigma G^T + V M V^T
+    fn propagate(&self, pose: &Pose, sigma: &Matrix<f64>, control: &U) -> (Pose, Matrix<f64>) {
+        let g = self.state_jacobian(pose, control);
+        let v = self.control_jacobian(pose, control);
+        let m = self.control_noise(pose, control);
+        let sigma = g * *sigma * g.transpose() + v * m * v.transpose();
+        (self.mean(pose, control), sigma)
+    }
+}
+
+
+// Differential drive: two wheels wheel_base apart, with the control the distance each
+// wheel rolled, (left, right). Each wheel slips by a gaussian whose std dev is slip times
+// the distance it rolled. Moves along the heading halfway through the turn, which is
+// accurate to second order in the turn.
+#[deriving(Show,Clone)]
+pub struct DiffDrive {
+    pub wheel_base: f32,
+    pub slip: f32,
+}
+
+
+impl DiffDrive {
+    pub fn new(wheel_base: f32, slip: f32) -> DiffDrive {
+        DiffDrive { wheel_base: wheel_base, slip: slip }
+    }
+
+    // (distance travelled, change in heading) for given wheel distances
+    pub fn forward(&self, (left, right): (f32, f32)) -> (f32, f32) {
+        ((left + right) / 2.0, (right - left) / self.wheel_base)
+    }
+
+    // the wheel distances that give a distance travelled and change in heading
+    pub fn inverse(&self, distance: f32, turn: f32) -> (f32, f32) {
+        let half = turn * self.wheel_base / 2.0;
+        (distance - half, distance + half)
+    }
+}
+
+
+impl MotionModel<Pose, (f32, f32)> for DiffDrive {
+    fn sample<R: Rng>(&self, pose: &Pose, &(left, right): &(f32, f32), rng: &mut R) -> Pose {
+        let left = left + gauss((self.slip * left.abs()) as f64, rng) as f32;
+        let right = right + gauss((self.slip * right.abs()) as f64, rng) as f32;
+        self.mean(pose, &(left, right))
+    }
+}
+
+
+impl LinearizedMotion<(f32, f32)> for DiffDrive {
+    fn mean(&self, pose: &Pose, &wheels: &(f32, f32)) -> Pose {
+        let (d, w) = self.forward(wheels);
+        let phi = pose.orientation + w / 2.0;
+        Pose::new(pose.x + d * phi.cos(), pose.y + d * phi.sin(), pose.orientation + w)
+    }
+
+    fn state_jacobian(&self, pose: &Pose, &wheels: &(f32, f32)) -> Matrix<f64> {
+        let (d, w) = self.forward(wheels);
+        let phi = (pose.orientation + w / 2.0) as f64;
+        let d = d as f64;
+        Matrix::new(vec![vec![1.0, 0.0, -d * phi.sin()],
+                         vec![0.0, 1.0, d * phi.cos()],
+                         vec![0.0, 0.0, 1.0]]).unwrap()
+    }
+
+    fn control_jacobian(&self, pose: &Pose, &wheels: &(f32, f32)) -> Matrix<f64> {
+        let (d, w) = self.forward(wheels);
+        let phi = (pose.orientation + w / 2.0) as f64;
+        let (d, b) = (d as f64, self.wheel_base as f64);
+        let (c, s) = (phi.cos(), phi.sin());
+        Matrix::new(vec![vec![0.5 * c + d * s / (2.0 * b), 0.5 * c - d * s / (2.0 * b)],
+                         vec![0.5 * s - d * c / (2.0 * b), 0.5 * s + d * c / (2.0 * b)],
+                         vec![-1.0 / b, 1.0 / b]]).unwrap()
+    }
+
+    fn control_noise(&self, _: &Pose, &(left, right): &(f32, f32)) -> Matrix<f64> {
+        let k = self.slip as f64;
+        Matrix::new(vec![vec![(k * left as f64).powi(2), 0.0],
+                         vec![0.0, (k * right as f64).powi(2)]]).unwrap()
+    }
+}
+
+
+// The odometry motion model (Thrun et al., Probabilistic Robotics 5.4). The control is a
+// pair of poses reported by the robot's own odometry, before and after the step, which is
+// split into a rotation, a translation and a second rotation. Their noise variances are
+//   rot1:  a1 rot1^2 + a2 trans^2
+//   trans: a3 trans^2 + a4 (rot1^2 + rot2^2)
+//   rot2:  a1 rot2^2 + a2 trans^2
+#[deriving(Show,Clone)]
+pub struct Odometry {
+    pub alpha: [f32, ..4],
+}
+
+
+impl Odometry {
+    pub fn new(a1: f32, a2: f32, a3: f32, a4: f32) -> Odometry {
+        Odometry { alpha: [a1, a2, a3, a4] }
+    }
+
+    // (rot1, trans, rot2) between two odometry poses
+    pub fn decompose(before: &Pose, after: &Pose) -> (f32, f32, f32) {
+        let (dx, dy) = (after.x - before.x, after.y - before.y);
+        let trans = (dx * dx + dy * dy).sqrt();
+        // turning on the spot says nothing about the direction of travel
+        let rot1 = if trans < 1e-6 { 0.0 } else { angle_diff(dy.atan2(dx), before.orientation) };
+        let rot2 = angle_diff(angle_diff(after.orientation, before.orientation), rot1);
+        (rot1, trans, rot2)
+    }
+
+    // variances of (rot1, trans, rot2)
+    pub fn variances(&self, (rot1, trans, rot2): (f32, f32, f32)) -> (f32, f32, f32) {
+        let a = self.alpha;
+        (a[0] * rot1 * rot1 + a[1] * trans * trans,
+         a[2] * trans * trans + a[3] * (rot1 * rot1 + rot2 * rot2),
+         a[0] * rot2 * rot2 + a[1] * trans * trans)
+    }
+}
+
+
+// pose after rot1, trans, rot2
+fn apply(pose: &Pose, (rot1, trans, rot2): (f32, f32, f32)) -> Pose {
+    let heading = pose.orientation + rot1;
+    Pose::new(pose.x + trans * heading.cos(), pose.y + trans * heading.sin(), heading + rot2)
+}
+
+
+impl MotionModel<Pose, (Pose, Pose)> for Odometry {
+    fn sample<R: Rng>(&self, pose: &Pose, &(ref before, ref after): &(Pose, Pose),
+            rng: &mut R) -> Pose {
+        let (rot1, trans, rot2) = Odometry::decompose(before, after);
+        let (v1, vt, v2) = self.variances((rot1, trans, rot2));
+        apply(pose, (rot1 - gauss(v1.sqrt() as f64, rng) as f32,
+                     trans - gauss(vt.sqrt() as f64, rng) as f32,
+                     rot2 - gauss(v2.sqrt() as f64, rng) as f32))
+    }
+}
+
+
+// The control the Jacobians are taken with respect to is (rot1, trans, rot2).
+impl LinearizedMotion<(Pose, Pose)> for Odometry {
+    fn mean(&self, pose: &Pose, &(ref before, ref after): &(Pose, Pose)) -> Pose {
+        apply(pose, Odometry::decompose(before, after))
+    }
+
+    fn state_jacobian(&self, pose: &Pose, &(ref before, ref after): &(Pose, Pose)) -> Matrix<f64> {
+        let (rot1, trans, _) = Odometry::decompose(before, after);
+        let heading = (pose.orientation + rot1) as f64;
+        let t = trans as f64;
+        Matrix::new(vec![vec![1.0, 0.0, -t * heading.sin()],
+                         vec![0.0, 1.0, t * heading.cos()],
+                         vec![0.0, 0.0, 1.0]]).unwrap()
+    }
+
+    fn control_jacobian(&self, pose: &Pose, &(ref before, ref after): &(Pose, Pose)) -> Matrix<f64> {
+        let (rot1, trans, _) = Odometry::decompose(before, after);
+        let heading = (pose.orientation + rot1) as f64;
+        let t = trans as f64;
+        Matrix::new(vec![vec![-t * heading.sin(), heading.cos(), 0.0],
+                         vec![t * heading.cos(), heading.sin(), 0.0],
+                         vec![1.0, 0.0, 1.0]]).unwrap()
+    }
+
+    fn control_noise(&self, _: &Pose, &(ref before, ref after): &(Pose, Pose)) -> Matrix<f64> {
+        let (v1, vt, v2) = self.variances(Odometry::decompose(before, after));
+        Matrix::new(vec![vec![v1 as f64, 0.0, 0.0],
+                         vec![0.0, vt as f64, 0.0],
+                         vec![0.0, 0.0, v2 as f64]]).unwrap()
+    }
+}
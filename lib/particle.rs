@@ -0,0 +1,368 @@
+use std::cmp::{Less, Greater};
+use std::num::Float;
+use std::rand::Rng;
+use std::collections::HashSet;
+use std::slice::{Found, NotFound};
+use resample::{Resampler, Wheel};
+use pose::{Pose, HasPose, SetPose};
+use kld::Kld;
+use amcl::{Augmented, StateSampler};
+use cluster::{Hypothesis, cluster};
+use regularize::Regularized;
+use models::gauss;
+
+
+// Samples where a state goes under a control.
+pub trait MotionModel<S, U> {
+    fn sample<R: Rng>(&self, state: &S, control: &U, rng: &mut R) -> S;
+}
+
+
+// How likely a measurement is from a state. Implemented in the log domain, since a
+// product of many small densities underflows.
+pub trait MeasurementModel<S, Z> {
+    // up to an additive constant; -inf when impossible
+    fn log_likelihood(&self, state: &S, z: &Z) -> f64;
+
+    fn likelihood(&self, state: &S, z: &Z) -> f64 {
+        self.log_likelihood(state, z).exp()
+    }
+}
+
+
+// How step moves, weighs and resamples the particles.
+#[deriving(Show,Clone,PartialEq)]
+pub enum Engine {
+    Bootstrap,               // move, weigh, resample when needed
+    Regularize(Regularized), // the same, but jitter the copies after resampling
+    Auxiliary,               // resample by a look-ahead first, then move and weigh
+}
+
+
+// Weighted particles over states S. Weights are kept normalized and carry over from
+// step to step; resampling only happens once they have degenerated.
+#[deriving(Show,Clone)]
+pub struct ParticleFilter<S> {
+    particles: Vec<S>,
+    log_weights: Vec<f64>, // normalized, so that the weights sum to one
+    weights: Vec<f64>,     // exp of log_weights
+    resampler: Resampler,
+    engine: Engine,
+    resample_threshold: f64, // resample when the effective sample size is below this * n
+    collapses: uint,         // measurements that no particle could explain
+    log_likelihood: f64,     // log of the weighted mean likelihood of the last measurement
+}
+
+
+impl<S: Clone> ParticleFilter<S> {
+    pub fn new(particles: Vec<S>) -> ParticleFilter<S> {
+        let n = particles.len();
+        ParticleFilter {
+            particles: particles,
+            log_weights: Vec::from_elem(n, -(n as f64).ln()),
+            weights: Vec::from_elem(n, 1.0 / n as f64),
+            collapses: 0,
+            log_likelihood: 0.0,
+            resampler: Wheel,
+            engine: Bootstrap,
+            resample_threshold: 0.5,
+        }
+    }
+
+    pub fn with_resampler(mut self, resampler: Resampler) -> ParticleFilter<S> {
+        self.resampler = resampler;
+        self
+    }
+
+    pub fn with_engine(mut self, engine: Engine) -> ParticleFilter<S> {
+        self.engine = engine;
+        self
+    }
+
+    // 1.0 resamples every step, 0.0 never
+    pub fn with_resample_threshold(mut self, threshold: f64) -> ParticleFilter<S> {
+        self.resample_threshold = threshold;
+        self
+    }
+
+    pub fn len(&self) -> uint {
+        self.particles.len()
+    }
+
+    pub fn particles(&self) -> &Vec<S> {
+        &self.particles
+    }
+
+    pub fn weights(&self) -> &Vec<f64> {
+        &self.weights
+    }
+
+    // move every particle
+    pub fn predict<U, M: MotionModel<S, U>, R: Rng>(&mut self, model: &M, control: &U,
+            rng: &mut R) {
+        for p in self.particles.iter_mut() {
+            *p = model.sample(&*p, control, rng);
+        }
+    }
+
+    // How many measurements were dropped because every particle gave them zero
+    // likelihood. A count that keeps rising means the particles have lost the robot.
+    pub fn collapses(&self) -> uint {
+        self.collapses
+    }
+
+    // How well the particles explained the last measurement: the log of their mean
+    // likelihood under the weights before it.
+    pub fn last_log_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    // Weighs every particle by the measurement. If no particle can explain it at all the
+    // weights would all be zero, and if one explains it with infinite likelihood (a model
+    // with no noise) they can't be normalized; either way the measurement is dropped and
+    // the weights are left as they were, and false is returned.
+    pub fn update<Z, M: MeasurementModel<S, Z>>(&mut self, model: &M, z: &Z) -> bool {
+        let log_weights: Vec<f64> = range(0, self.particles.len()).map(|i| {
+            self.log_weights[i] + finite_or_impossible(model.log_likelihood(&self.particles[i], z))
+        }).collect();
+        let total = log_sum_exp(&log_weights);
+        if !total.is_finite() {
+            self.collapses += 1;
+            self.log_likelihood = Float::neg_infinity();
+            return false;
+        }
+        self.log_likelihood = total;
+        self.log_weights = log_weights.iter().map(|&lw| lw - total).collect();
+        self.weights = self.log_weights.iter().map(|&lw| lw.exp()).collect();
+        true
+    }
+
+    // draw a new, equally weighted set in proportion to the weights
+    pub fn resample<R: Rng>(&mut self, rng: &mut R) {
+        let n = self.particles.len();
+        let indices = self.resampler.indices(&self.weights, n, rng);
+        self.particles = indices.iter().map(|&i| self.particles[i].clone()).collect();
+        self.log_weights = Vec::from_elem(n, -(n as f64).ln());
+        self.weights = Vec::from_elem(n, 1.0 / n as f64);
+    }
+
+    // 1 / sum(w^2): n for equal weights, 1 when one particle has all the weight
+    pub fn effective_sample_size(&self) -> f64 {
+        1.0 / self.weights.iter().fold(0.0, |a, &w| a + w * w)
+    }
+
+    // resamples if the effective sample size has dropped below the threshold; returns
+    // whether it did
+    pub fn resample_if_needed<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let n = self.particles.len() as f64;
+        if self.effective_sample_size() < self.resample_threshold * n {
+            self.resample(rng);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+
+
+impl<S: Clone + HasPose> ParticleFilter<S> {
+    // the modes of the cloud, most weighted first
+    pub fn hypotheses(&self, xy_resolution: f32, angle_resolution: f32) -> Vec<Hypothesis> {
+        cluster(&self.particles, &self.weights, xy_resolution, angle_resolution)
+    }
+
+    // KLD-sampling (Fox 2003): draws particles in proportion to the weights and moves
+    // each as it is drawn, until the moved ones cover enough pose bins for kld; returns
+    // the new particle count. Binning after the motion sizes the set for the spread the
+    // motion adds. Draws are independent, so the configured resampler is not used.
+    pub fn predict_kld<U, M: MotionModel<S, U>, R: Rng>(&mut self, motion: &M, control: &U,
+            kld: &Kld, rng: &mut R) -> uint {
+        if self.particles.len() == 0 {
+            return 0;
+        }
+        let mut cs = Vec::with_capacity(self.weights.len());
+        let mut s = 0.0;
+        for &w in self.weights.iter() {
+            s += w;
+            cs.push(s);
+        }
+        let mut particles = Vec::new();
+        let mut bins = HashSet::new();
+        loop {
+            let u = rng.gen::<f64>() * s;
+            // first particle whose cumulative weight is above u; a NaN weight must not
+            // fail the search, so no partial_cmp
+            let i = match cs.binary_search(|&c| if c <= u { Less } else { Greater }) {
+                Found(i) | NotFound(i) => i,
+            };
+            let i = if i < cs.len() { i } else { cs.len() - 1 };
+            let p = motion.sample(&self.particles[i], control, rng);
+            bins.insert(kld.bin(&p.pose()));
+            particles.push(p);
+            let n = particles.len();
+            if n >= kld.max_particles || n >= kld.required(bins.len()) {
+                break;
+            }
+        }
+        let n = particles.len();
+        self.particles = particles;
+        self.log_weights = Vec::from_elem(n, -(n as f64).ln());
+        self.weights = Vec::from_elem(n, 1.0 / n as f64);
+        n
+    }
+
+    // draw and move with KLD-sampling, then weigh; returns the particle count
+    pub fn step_kld<U, Z, M: MotionModel<S, U>, N: MeasurementModel<S, Z>, R: Rng>(&mut self,
+            motion: &M, control: &U, measurement: &N, z: &Z, kld: &Kld, rng: &mut R) -> uint {
+        let n = self.predict_kld(motion, control, kld, rng);
+        self.update(measurement, z);
+        n
+    }
+}
+
+
+impl<S: Clone> ParticleFilter<S> {
+    // Folds the last measurement into augmented's likelihood averages. Then, if the
+    // weights have degenerated enough to resample, resamples and swaps each particle for
+    // a fresh one from sampler with the chance augmented gives; returns how many were
+    // swapped.
+    pub fn resample_augmented<G: StateSampler<S>, R: Rng>(&mut self, augmented: &mut Augmented,
+            sampler: &G, rng: &mut R) -> uint {
+        augmented.observe(self.log_likelihood);
+        if !self.resample_if_needed(rng) {
+            return 0;
+        }
+        let p = augmented.injection_probability();
+        let mut injected = 0;
+        if p > 0.0 {
+            for x in self.particles.iter_mut() {
+                if rng.gen::<f64>() < p {
+                    *x = sampler.sample(rng);
+                    injected += 1;
+                }
+            }
+        }
+        injected
+    }
+
+    // move, weigh, then resample with random injection when needed; returns how many
+    // were injected
+    pub fn step_augmented<U, Z, M: MotionModel<S, U>, N: MeasurementModel<S, Z>,
+            G: StateSampler<S>, R: Rng>(&mut self, motion: &M, control: &U, measurement: &N,
+            z: &Z, augmented: &mut Augmented, sampler: &G, rng: &mut R) -> uint {
+        self.predict(motion, control, rng);
+        self.update(measurement, z);
+        self.resample_augmented(augmented, sampler, rng)
+    }
+
+    // Auxiliary particle filter step (Pitt & Shephard 1999). Every particle first looks
+    // ahead with one draw from the motion model, and the particles are resampled by how
+    // well their look-aheads explain z, so effort goes to those likely to survive it. The
+    // chosen ones then move afresh and are weighted by how much better or worse they did
+    // than their look-ahead. Returns false, after just moving the particles, when no
+    // look-ahead can explain z.
+    pub fn step_auxiliary<U, Z, M: MotionModel<S, U>, N: MeasurementModel<S, Z>, R: Rng>(
+            &mut self, motion: &M, control: &U, measurement: &N, z: &Z, rng: &mut R) -> bool {
+        let n = self.particles.len();
+        let ahead: Vec<f64> = range(0, n).map(|i| {
+            let x = motion.sample(&self.particles[i], control, rng);
+            finite_or_impossible(measurement.log_likelihood(&x, z))
+        }).collect();
+        let first: Vec<f64> = range(0, n).map(|i| self.log_weights[i] + ahead[i]).collect();
+        let total = log_sum_exp(&first);
+        if !total.is_finite() {
+            self.collapses += 1;
+            self.log_likelihood = Float::neg_infinity();
+            self.predict(motion, control, rng);
+            return false;
+        }
+        let lambda: Vec<f64> = first.iter().map(|&l| (l - total).exp()).collect();
+        let indices = self.resampler.indices(&lambda, n, rng);
+        let particles: Vec<S> = indices.iter().map(|&k| {
+            motion.sample(&self.particles[k], control, rng)
+        }).collect();
+        let log_weights: Vec<f64> = range(0, n).map(|j| {
+            finite_or_impossible(measurement.log_likelihood(&particles[j], z)) - ahead[indices[j]]
+        }).collect();
+        self.particles = particles;
+        let second = log_sum_exp(&log_weights);
+        if second.is_nan() || second == Float::neg_infinity() {
+            self.collapses += 1;
+            self.log_likelihood = Float::neg_infinity();
+            self.log_weights = Vec::from_elem(n, -(n as f64).ln());
+            self.weights = Vec::from_elem(n, 1.0 / n as f64);
+            return false;
+        }
+        self.log_likelihood = total + second - (n as f64).ln();
+        self.log_weights = log_weights.iter().map(|&lw| lw - second).collect();
+        self.weights = self.log_weights.iter().map(|&lw| lw.exp()).collect();
+        true
+    }
+}
+
+
+impl<S: Clone + SetPose> ParticleFilter<S> {
+    // one full cycle the way the engine does it
+    pub fn step<U, Z, M: MotionModel<S, U>, N: MeasurementModel<S, Z>, R: Rng>(&mut self,
+            motion: &M, control: &U, measurement: &N, z: &Z, rng: &mut R) {
+        match self.engine.clone() {
+            Bootstrap => {
+                self.predict(motion, control, rng);
+                self.update(measurement, z);
+                self.resample_if_needed(rng);
+            }
+            Regularize(regularized) => {
+                self.step_regularized(motion, control, measurement, z, &regularized, rng);
+            }
+            Auxiliary => {
+                self.step_auxiliary(motion, control, measurement, z, rng);
+            }
+        }
+    }
+
+    // Resamples, then moves every particle by a draw from the kernel, which is fitted to
+    // the cloud as it was before resampling.
+    pub fn resample_regularized<R: Rng>(&mut self, regularized: &Regularized, rng: &mut R) {
+        let (sx, sy, sa) = regularized.kernel(&self.particles, &self.weights);
+        self.resample(rng);
+        for p in self.particles.iter_mut() {
+            let pose = p.pose();
+            p.set_pose(&Pose::new(pose.x + gauss(sx, rng) as f32,
+                                  pose.y + gauss(sy, rng) as f32,
+                                  pose.orientation + gauss(sa, rng) as f32));
+        }
+    }
+
+    // move, weigh, then resample with jitter when needed; returns whether it resampled
+    pub fn step_regularized<U, Z, M: MotionModel<S, U>, N: MeasurementModel<S, Z>, R: Rng>(
+            &mut self, motion: &M, control: &U, measurement: &N, z: &Z,
+            regularized: &Regularized, rng: &mut R) -> bool {
+        self.predict(motion, control, rng);
+        self.update(measurement, z);
+        let n = self.particles.len() as f64;
+        if self.effective_sample_size() < self.resample_threshold * n {
+            self.resample_regularized(regularized, rng);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+
+// A NaN log likelihood (say from a model with zero noise) counts as impossible for that
+// particle alone, rather than spoiling the normalization for all of them.
+fn finite_or_impossible(log_likelihood: f64) -> f64 {
+    if log_likelihood.is_nan() { Float::neg_infinity() } else { log_likelihood }
+}
+
+
+// log(sum(exp(xs))), shifted by the largest term so that it neither under- nor overflows
+pub fn log_sum_exp(xs: &Vec<f64>) -> f64 {
+    let m = xs.iter().fold(Float::neg_infinity(), |a: f64, &x| if x > a { x } else { a });
+    if m == Float::neg_infinity() || m == Float::infinity() {
+        return m;
+    }
+    m + xs.iter().fold(0.0, |a, &x| a + (x - m).exp()).ln()
+}
@@ -0,0 +1,71 @@
+use std::num::Float;
+
+
+// Position and heading in the plane; orientation is in [0, 2 pi).
+#[deriving(Show,Clone,PartialEq)]
+pub struct Pose {
+    pub x: f32,
+    pub y: f32,
+    pub orientation: f32,
+}
+
+
+impl Pose {
+    pub fn new(x: f32, y: f32, orientation: f32) -> Pose {
+        Pose {
+            x: x,
+            y: y,
+            orientation: modulo(orientation, Float::two_pi()),
+        }
+    }
+
+    pub fn distance_to(&self, (x, y): (f32, f32)) -> f32 {
+        ((self.x - x).powi(2) + (self.y - y).powi(2)).sqrt()
+    }
+
+    // direction of (x, y) relative to the heading, in [0, 2 pi)
+    pub fn bearing_to(&self, (x, y): (f32, f32)) -> f32 {
+        modulo((y - self.y).atan2(x - self.x) - self.orientation, Float::two_pi())
+    }
+}
+
+
+// Anything with a pose, so that measurement models can weigh the exercises' robots.
+pub trait HasPose {
+    fn pose(&self) -> Pose;
+}
+
+
+impl HasPose for Pose {
+    fn pose(&self) -> Pose {
+        self.clone()
+    }
+}
+
+
+// States whose pose can be moved, for filters that perturb particles directly.
+pub trait SetPose: HasPose {
+    fn set_pose(&mut self, pose: &Pose);
+}
+
+
+impl SetPose for Pose {
+    fn set_pose(&mut self, pose: &Pose) {
+        *self = pose.clone();
+    }
+}
+
+
+// n mod m, in [0, m)
+pub fn modulo(n: f32, m: f32) -> f32 {
+    let mut k = n % m;
+    while k < 0.0 { k += m };
+    // a tiny negative n rounds up to exactly m
+    if k >= m { 0.0 } else { k }
+}
+
+
+// a - b wrapped into [-pi, pi)
+pub fn angle_diff(a: f32, b: f32) -> f32 {
+    modulo(a - b + Float::pi(), Float::two_pi()) - Float::pi()
+}
@@ -0,0 +1,173 @@
+use std::cmp::{min, max};
+use std::iter::AdditiveIterator;
+use std::num::{Float, zero, cast};
+use matrix::{Matrix, MatrixError, DimensionMismatch, Singular, lit};
+
+
+// AP = QR by householder reflections. Columns are pivoted by remaining norm so that
+// the diagonal of R decreases and reveals the rank.
+#[deriving(Show,Clone)]
+pub struct Qr<T> {
+    r: Matrix<T>,
+    vs: Vec<Vec<T>>, // householder vectors, vs[k] acts on rows k..
+    taus: Vec<T>,
+    perm: Vec<uint>, // column j of R is column perm[j] of A
+    rank: uint,
+}
+
+
+impl<T: Float> Qr<T> {
+    pub fn new(a: &Matrix<T>) -> Qr<T> {
+        let m = a.dimx;
+        let n = a.dimy;
+        let mut r = a.clone();
+        let mut perm = Vec::from_fn(n, |j| j);
+        let mut vs = Vec::new();
+        let mut taus = Vec::new();
+
+        for k in range(0, min(m, n)) {
+            // pivot the column with the largest remaining norm
+            let mut p = k;
+            let mut norm2 = zero();
+            for j in range(k, n) {
+                let s = range(k, m).map(|i| r[(i, j)] * r[(i, j)]).sum();
+                if s > norm2 {
+                    norm2 = s;
+                    p = j;
+                }
+            }
+            if p != k {
+                r.swap_cols(p, k);
+                perm.as_mut_slice().swap(p, k);
+            }
+
+            let mut v: Vec<T> = range(k, m).map(|i| r[(i, k)]).collect();
+            if norm2 == zero() {
+                vs.push(v);
+                taus.push(zero());
+                continue;
+            }
+
+            // reflect column k onto alpha * e1
+            let norm = norm2.sqrt();
+            let alpha = if v[0] > zero() { -norm } else { norm };
+            *v.get_mut(0) = v[0] - alpha;
+            let tau = lit::<T>(2.0) / v.iter().map(|&x| x * x).sum();
+
+            for j in range(k+1, n) {
+                let s = tau * range(k, m).map(|i| v[i - k] * r[(i, j)]).sum();
+                for i in range(k, m) {
+                    r[(i, j)] = r[(i, j)] - s * v[i - k];
+                }
+            }
+            r[(k, k)] = alpha;
+            for i in range(k+1, m) {
+                r[(i, k)] = zero();
+            }
+
+            vs.push(v);
+            taus.push(tau);
+        }
+
+        // diagonal entries this small are round-off
+        let tol = if min(m, n) > 0 {
+            r[(0, 0)].abs() * Float::epsilon() * cast(max(m, n)).unwrap()
+        } else {
+            zero()
+        };
+        let mut rank = 0;
+        while rank < min(m, n) && r[(rank, rank)].abs() > tol {
+            rank += 1;
+        }
+
+        Qr {
+            r: r,
+            vs: vs,
+            taus: taus,
+            perm: perm,
+            rank: rank,
+        }
+    }
+
+    pub fn rank(&self) -> uint {
+        self.rank
+    }
+
+    pub fn r(&self) -> Matrix<T> {
+        self.r.clone()
+    }
+
+    pub fn perm(&self) -> Vec<uint> {
+        self.perm.clone()
+    }
+
+    // trans(q) * b
+    pub fn apply_qt(&self, b: &Matrix<T>) -> Matrix<T> {
+        assert!(b.dimx == self.r.dimx);
+        let m = b.dimx;
+        let mut res = b.clone();
+        for k in range(0, self.vs.len()) {
+            let v = &self.vs[k];
+            let tau = self.taus[k];
+            for c in range(0, b.dimy) {
+                let s = tau * range(k, m).map(|i| v[i - k] * res[(i, c)]).sum();
+                for i in range(k, m) {
+                    res[(i, c)] = res[(i, c)] - s * v[i - k];
+                }
+            }
+        }
+        res
+    }
+
+    pub fn q(&self) -> Matrix<T> {
+        self.apply_qt(&Matrix::identity(self.r.dimx)).transpose()
+    }
+
+    // x minimizing |a * x - b|; a needs full column rank
+    pub fn least_squares(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let m = self.r.dimx;
+        let n = self.r.dimy;
+        if b.dimx != m {
+            return Err(DimensionMismatch((m, b.dimy), (b.dimx, b.dimy)));
+        }
+        // rank deficient
+        if self.rank < n {
+            return Err(Singular);
+        }
+
+        let y = self.apply_qt(b);
+        let mut x = Matrix::zero(n, b.dimy);
+        for c in range(0, b.dimy) {
+            // backward: r * z = y, then undo the column pivoting
+            let mut z = Vec::from_elem(n, zero::<T>());
+            for i_ in range(0, n) {
+                let i = n - (i_ + 1);
+                let mut s = y[(i, c)];
+                for k in range(i+1, n) {
+                    s = s - self.r[(i, k)] * z[k];
+                }
+                *z.get_mut(i) = s / self.r[(i, i)];
+            }
+            for j in range(0, n) {
+                x[(self.perm[j], c)] = z[j];
+            }
+        }
+        Ok(x)
+    }
+}
+
+
+impl<T: Float> Matrix<T> {
+    pub fn qr(&self) -> Qr<T> {
+        Qr::new(self)
+    }
+
+    pub fn rank(&self) -> uint {
+        self.qr().rank()
+    }
+
+    // x minimizing |self * x - b|
+    pub fn least_squares(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.qr().least_squares(b)
+    }
+}
@@ -0,0 +1,190 @@
+use std::num::{Float, FloatMath};
+use grid::OccupancyGrid;
+use models::log_gaussian;
+use particle::MeasurementModel;
+use pose::HasPose;
+
+
+// n beam directions relative to the heading, evenly spread over fov radians and
+// centred on straight ahead
+pub fn scan_angles(n: uint, fov: f32) -> Vec<f32> {
+    if n == 1 {
+        return vec![0.0];
+    }
+    range(0, n).map(|i| -fov / 2.0 + fov * i as f32 / (n - 1) as f32).collect()
+}
+
+
+// Beam model for a range finder (Thrun et al., Probabilistic Robotics 6.3). Each range
+// is a mixture of a noisy hit on the wall the ray reaches, a short reading off something
+// unmapped, a max-range reading when nothing returns, and uniform noise.
+#[deriving(Show,Clone)]
+pub struct BeamModel {
+    pub map: OccupancyGrid,
+    pub angles: Vec<f32>,  // beam directions relative to the heading
+    pub max_range: f32,
+    pub sigma_hit: f32,    // std dev of a hit around the true range
+    pub lambda_short: f32, // decay rate of short readings
+    pub z_hit: f32,        // mixture weights, summing to one
+    pub z_short: f32,
+    pub z_max: f32,
+    pub z_rand: f32,
+}
+
+
+impl BeamModel {
+    pub fn new(map: OccupancyGrid, angles: Vec<f32>, max_range: f32, sigma_hit: f32) -> BeamModel {
+        BeamModel {
+            map: map,
+            angles: angles,
+            max_range: max_range,
+            sigma_hit: sigma_hit,
+            lambda_short: 0.1,
+            z_hit: 0.8,
+            z_short: 0.1,
+            z_max: 0.05,
+            z_rand: 0.05,
+        }
+    }
+
+    pub fn with_weights(mut self, z_hit: f32, z_short: f32, z_max: f32,
+            z_rand: f32) -> BeamModel {
+        let total = z_hit + z_short + z_max + z_rand;
+        self.z_hit = z_hit / total;
+        self.z_short = z_short / total;
+        self.z_max = z_max / total;
+        self.z_rand = z_rand / total;
+        self
+    }
+
+    // the noiseless ranges from a pose, by ray casting
+    pub fn expected<S: HasPose>(&self, state: &S) -> Vec<f32> {
+        expected(&self.map, &self.angles, self.max_range, state)
+    }
+
+    // density of reading z when the ray reaches a wall at z_star
+    pub fn beam_prob(&self, z: f32, z_star: f32) -> f64 {
+        let (z, z_star, max) = (z as f64, z_star as f64, self.max_range as f64);
+        let sigma = self.sigma_hit as f64;
+        let lambda = self.lambda_short as f64;
+        let mut p = 0.0;
+        if z >= 0.0 && z < max {
+            // the gaussian cut to [0, max) and renormalized
+            let mass = normal_cdf((max - z_star) / sigma) - normal_cdf(-z_star / sigma);
+            if mass > 0.0 {
+                p += self.z_hit as f64 * log_gaussian(z_star, sigma, z).exp() / mass;
+            }
+            if z <= z_star && z_star > 0.0 {
+                p += self.z_short as f64 * lambda * (-lambda * z).exp() /
+                    (1.0 - (-lambda * z_star).exp());
+            }
+            p += self.z_rand as f64 / max;
+        } else if z >= max {
+            p += self.z_max as f64;
+        }
+        p
+    }
+}
+
+
+impl<S: HasPose> MeasurementModel<S, Vec<f32>> for BeamModel {
+    fn log_likelihood(&self, state: &S, z: &Vec<f32>) -> f64 {
+        assert!(z.len() == self.angles.len());
+        let mut res = 0.0;
+        for (&z_star, &zi) in self.expected(state).iter().zip(z.iter()) {
+            res += self.beam_prob(zi, z_star).ln();
+        }
+        res
+    }
+}
+
+
+// Likelihood field model (Probabilistic Robotics 6.4). Instead of casting rays, each
+// beam's end point is scored by its distance to the nearest obstacle, which is looked up
+// in a field computed once for the map. Much cheaper than the beam model, and smooth
+// in the pose.
+#[deriving(Show,Clone)]
+pub struct LikelihoodFieldModel {
+    pub map: OccupancyGrid,
+    pub angles: Vec<f32>,
+    pub max_range: f32,
+    pub sigma_hit: f32,
+    pub z_hit: f32,
+    pub z_rand: f32,
+    field: Vec<f32>, // distance to the nearest obstacle, per cell
+}
+
+
+impl LikelihoodFieldModel {
+    pub fn new(map: OccupancyGrid, angles: Vec<f32>, max_range: f32,
+            sigma_hit: f32) -> LikelihoodFieldModel {
+        let field = map.distance_field();
+        LikelihoodFieldModel {
+            map: map,
+            angles: angles,
+            max_range: max_range,
+            sigma_hit: sigma_hit,
+            z_hit: 0.9,
+            z_rand: 0.1,
+            field: field,
+        }
+    }
+
+    pub fn with_weights(mut self, z_hit: f32, z_rand: f32) -> LikelihoodFieldModel {
+        self.z_hit = z_hit / (z_hit + z_rand);
+        self.z_rand = z_rand / (z_hit + z_rand);
+        self
+    }
+
+    pub fn expected<S: HasPose>(&self, state: &S) -> Vec<f32> {
+        expected(&self.map, &self.angles, self.max_range, state)
+    }
+
+    // distance from a point to the nearest obstacle; off the map it is the edge itself
+    pub fn distance(&self, x: f32, y: f32) -> f32 {
+        let (i, j) = self.map.cell(x, y);
+        if i < 0 || j < 0 || i as uint >= self.map.width || j as uint >= self.map.height {
+            return 0.0;
+        }
+        self.field[i as uint * self.map.height + j as uint]
+    }
+}
+
+
+impl<S: HasPose> MeasurementModel<S, Vec<f32>> for LikelihoodFieldModel {
+    fn log_likelihood(&self, state: &S, z: &Vec<f32>) -> f64 {
+        assert!(z.len() == self.angles.len());
+        let pose = state.pose();
+        let rand = self.z_rand as f64 / self.max_range as f64;
+        let mut res = 0.0;
+        for (&a, &zi) in self.angles.iter().zip(z.iter()) {
+            // max-range readings say nothing about where the walls are
+            if zi >= self.max_range {
+                continue;
+            }
+            let heading = pose.orientation + a;
+            let d = self.distance(pose.x + zi * heading.cos(), pose.y + zi * heading.sin());
+            let hit = log_gaussian(0.0, self.sigma_hit as f64, d as f64).exp();
+            res += (self.z_hit as f64 * hit + rand).ln();
+        }
+        res
+    }
+}
+
+
+fn expected<S: HasPose>(map: &OccupancyGrid, angles: &Vec<f32>, max_range: f32,
+        state: &S) -> Vec<f32> {
+    let pose = state.pose();
+    angles.iter().map(|&a| map.ray_cast(pose.x, pose.y, pose.orientation + a, max_range)).collect()
+}
+
+
+// P(X <= x) for a standard normal X, from the Abramowitz & Stegun 7.1.26 erf, good to
+// about 1e-7
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / 2.0f64.sqrt());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 +
+        t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x / 2.0).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
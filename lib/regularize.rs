@@ -0,0 +1,67 @@
+use std::num::{Float, FloatMath};
+use pose::{HasPose, angle_diff};
+
+
+// Settings for the regularized particle filter (Musso et al. 2001). After resampling,
+// each copy is moved by a draw from a gaussian kernel, so that duplicates of one particle
+// spread out instead of staying stacked on the same state. The kernel's std dev in each
+// of x, y and orientation is the weighted std dev of the particles there times the
+// bandwidth; Silverman's rule of thumb gives the bandwidth that is best when the cloud is
+// gaussian.
+#[deriving(Show,Clone,PartialEq)]
+pub struct Regularized {
+    pub scale: f64, // times the Silverman bandwidth; below 1 for clouds with several modes
+}
+
+
+impl Regularized {
+    pub fn new() -> Regularized {
+        Regularized { scale: 1.0 }
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Regularized {
+        self.scale = scale;
+        self
+    }
+
+    // (4 / ((d + 2) n))^(1 / (d + 4)) for the d = 3 dimensions of a pose
+    pub fn bandwidth(&self, n: uint) -> f64 {
+        self.scale * (4.0 / (5.0 * n as f64)).powf(1.0 / 7.0)
+    }
+
+    // the kernel's std devs in (x, y, orientation)
+    pub fn kernel<S: HasPose>(&self, particles: &Vec<S>, weights: &Vec<f64>) -> (f64, f64, f64) {
+        let (sx, sy, sa) = spread(particles, weights);
+        let h = self.bandwidth(particles.len());
+        (h * sx, h * sy, h * sa)
+    }
+}
+
+
+// Weighted std devs of (x, y, orientation); orientation is taken about its circular mean,
+// so a cloud straddling 0 is not mistaken for one spread over the whole circle.
+pub fn spread<S: HasPose>(particles: &Vec<S>, weights: &Vec<f64>) -> (f64, f64, f64) {
+    assert!(particles.len() == weights.len());
+    let total = weights.iter().fold(0.0, |a, &w| a + w);
+    if particles.len() == 0 || total <= 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let (mut mx, mut my, mut c, mut s) = (0.0, 0.0, 0.0, 0.0);
+    for (p, &w) in particles.iter().zip(weights.iter()) {
+        let pose = p.pose();
+        mx += w * pose.x as f64;
+        my += w * pose.y as f64;
+        c += w * (pose.orientation as f64).cos();
+        s += w * (pose.orientation as f64).sin();
+    }
+    let (mx, my) = (mx / total, my / total);
+    let ma = s.atan2(c) as f32;
+    let (mut vx, mut vy, mut va) = (0.0, 0.0, 0.0);
+    for (p, &w) in particles.iter().zip(weights.iter()) {
+        let pose = p.pose();
+        vx += w * (pose.x as f64 - mx).powi(2);
+        vy += w * (pose.y as f64 - my).powi(2);
+        va += w * (angle_diff(pose.orientation, ma) as f64).powi(2);
+    }
+    ((vx / total).sqrt(), (vy / total).sqrt(), (va / total).sqrt())
+}
@@ -0,0 +1,116 @@
+use std::num::Float;
+use std::rand::Rng;
+use matrix::descending;
+
+
+// How to pick the particles that survive a resampling. All of them copy particle i
+// n * w_i times on average; they differ in how much the counts scatter around that.
+#[deriving(Show,Clone,PartialEq)]
+pub enum Resampler {
+    Wheel,       // the lecture's resampling wheel
+    Multinomial, // n independent draws
+    Stratified,  // one draw in each of n equal strata
+    Systematic,  // one draw, then evenly spaced
+    Residual,    // floor(n * w_i) copies, multinomial draws for the rest
+}
+
+
+impl Resampler {
+    // n indices into weights, drawn in proportion to them. The weights need not sum to one.
+    // Weights that sum to zero, or to infinity or NaN, can't be drawn in proportion to,
+    // so then every index is equally likely.
+    pub fn indices<R: Rng>(&self, weights: &Vec<f64>, n: uint, rng: &mut R) -> Vec<uint> {
+        if weights.len() == 0 || n == 0 {
+            return Vec::new();
+        }
+        let total = weights.iter().fold(0.0, |a, &w| a + w);
+        if !(total > 0.0) || !total.is_finite() {
+            return self.indices(&Vec::from_elem(weights.len(), 1.0), n, rng);
+        }
+        match *self {
+            Wheel => wheel(weights, n, rng),
+            Multinomial => {
+                let cs = cumsum(weights);
+                let total = cs[cs.len() - 1];
+                let mut us: Vec<f64> = range(0, n).map(|_| rng.gen::<f64>() * total).collect();
+                // ascending; reversing the arguments of the NaN-last order can't panic
+                us.sort_by(|a, b| descending(b, a));
+                select(&cs, &us)
+            }
+            Stratified => {
+                let cs = cumsum(weights);
+                let total = cs[cs.len() - 1];
+                let us: Vec<f64> = range(0, n).map(|i| {
+                    (i as f64 + rng.gen::<f64>()) / n as f64 * total
+                }).collect();
+                select(&cs, &us)
+            }
+            Systematic => {
+                let cs = cumsum(weights);
+                let total = cs[cs.len() - 1];
+                let u = rng.gen::<f64>();
+                let us: Vec<f64> = range(0, n).map(|i| (i as f64 + u) / n as f64 * total).collect();
+                select(&cs, &us)
+            }
+            Residual => residual(weights, n, rng),
+        }
+    }
+}
+
+
+fn cumsum(weights: &Vec<f64>) -> Vec<f64> {
+    let mut s = 0.0;
+    weights.iter().map(|&w| { s += w; s }).collect()
+}
+
+
+// for each of the sorted points us, the index of the cumulative weight it falls under
+fn select(cs: &Vec<f64>, us: &Vec<f64>) -> Vec<uint> {
+    let mut res = Vec::with_capacity(us.len());
+    let mut i = 0;
+    for &u in us.iter() {
+        while i < cs.len() - 1 && cs[i] <= u {
+            i += 1;
+        }
+        res.push(i);
+    }
+    res
+}
+
+
+// Walk around the wheel from a random particle, stepping up to twice the largest
+// weight each time, starting a random offset in.
+fn wheel<R: Rng>(weights: &Vec<f64>, n: uint, rng: &mut R) -> Vec<uint> {
+    let len = weights.len();
+    let mw = weights.iter().fold(0.0, |a, &w| if w > a { w } else { a });
+    let mut res = Vec::with_capacity(n);
+    let mut index = rng.gen_range(0, len);
+    let mut beta = rng.gen::<f64>() * 2.0 * mw;
+    for _ in range(0, n) {
+        while beta > weights[index] {
+            beta -= weights[index];
+            index = (index + 1) % len;
+        }
+        res.push(index);
+        beta += rng.gen::<f64>() * 2.0 * mw;
+    }
+    res
+}
+
+
+fn residual<R: Rng>(weights: &Vec<f64>, n: uint, rng: &mut R) -> Vec<uint> {
+    let total = weights.iter().fold(0.0, |a, &w| a + w);
+    let mut res = Vec::with_capacity(n);
+    let mut rest = Vec::with_capacity(weights.len());
+    for i in range(0, weights.len()) {
+        let expected = weights[i] / total * n as f64;
+        let copies = expected.floor();
+        for _ in range(0, copies as uint) {
+            res.push(i);
+        }
+        rest.push(expected - copies);
+    }
+    let m = n - res.len();
+    res.extend(Multinomial.indices(&rest, m, rng).into_iter());
+    res
+}
@@ -0,0 +1,30 @@
+use std::os;
+use std::rand::{SeedableRng, StdRng};
+
+
+pub static DEFAULT_SEED: uint = 373;
+
+
+// A generator that gives the same numbers every time for the same seed, so that a
+// simulation can be replayed exactly.
+pub fn seeded(seed: uint) -> StdRng {
+    SeedableRng::from_seed([seed].as_slice())
+}
+
+
+// The seed in the CS373_SEED environment variable, or DEFAULT_SEED when it is unset.
+pub fn seed_from_env() -> uint {
+    match os::getenv("CS373_SEED") {
+        Some(s) => match from_str(s.as_slice().trim()) {
+            Some(seed) => seed,
+            None => fail!("CS373_SEED must be a non-negative integer, not {}", s),
+        },
+        None => DEFAULT_SEED,
+    }
+}
+
+
+// what the exercises start from
+pub fn from_env() -> StdRng {
+    seeded(seed_from_env())
+}
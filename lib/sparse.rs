@@ -0,0 +1,333 @@
+use std::collections::{HashSet, PriorityQueue};
+use std::num::{Float, zero};
+use matrix::{Matrix, MatrixError, DimensionMismatch, NotPositiveDefinite};
+
+
+// Assembles a sparse matrix one entry at a time. Entries added more than once at the
+// same position are summed, so factors can be added to an information matrix as-is.
+#[deriving(Show,Clone)]
+pub struct Triplets<T> {
+    pub dimx: uint,
+    pub dimy: uint,
+    entries: Vec<(uint, uint, T)>,
+}
+
+
+impl<T: Float> Triplets<T> {
+    pub fn new(dimx: uint, dimy: uint) -> Triplets<T> {
+        Triplets {
+            dimx: dimx,
+            dimy: dimy,
+            entries: Vec::new(),
+        }
+    }
+
+    // value[i][j] += v
+    pub fn add(&mut self, i: uint, j: uint, v: T) {
+        assert!(i < self.dimx && j < self.dimy);
+        self.entries.push((i, j, v));
+    }
+
+    pub fn to_csr(&self) -> SparseMatrix<T> {
+        // bucket by row, then merge duplicate columns within each row
+        let mut rows: Vec<Vec<(uint, T)>> = Vec::from_elem(self.dimx, Vec::new());
+        for &(i, j, v) in self.entries.iter() {
+            rows.get_mut(i).push((j, v));
+        }
+
+        let mut row_ptr = Vec::with_capacity(self.dimx + 1);
+        let mut col_idx = Vec::new();
+        let mut values = Vec::new();
+        row_ptr.push(0);
+        for row in rows.iter_mut() {
+            row.sort_by(|a, b| a.0.cmp(&b.0));
+            for &(j, v) in row.iter() {
+                if values.len() > row_ptr[row_ptr.len() - 1] && col_idx[col_idx.len() - 1] == j {
+                    let last = values.len() - 1;
+                    *values.get_mut(last) = values[last] + v;
+                } else {
+                    col_idx.push(j);
+                    values.push(v);
+                }
+            }
+            row_ptr.push(values.len());
+        }
+
+        SparseMatrix {
+            dimx: self.dimx,
+            dimy: self.dimy,
+            row_ptr: row_ptr,
+            col_idx: col_idx,
+            values: values,
+        }
+    }
+}
+
+
+// Compressed sparse rows: the entries of row i are at row_ptr[i]..row_ptr[i+1].
+#[deriving(Show,Clone)]
+pub struct SparseMatrix<T> {
+    pub dimx: uint,
+    pub dimy: uint,
+    row_ptr: Vec<uint>,
+    col_idx: Vec<uint>,
+    values: Vec<T>,
+}
+
+
+impl<T: Float> SparseMatrix<T> {
+    pub fn nnz(&self) -> uint {
+        self.values.len()
+    }
+
+    // (column, value) of the entries in row i
+    pub fn row(&self, i: uint) -> Vec<(uint, T)> {
+        range(self.row_ptr[i], self.row_ptr[i + 1]).map(|p| (self.col_idx[p], self.values[p])).collect()
+    }
+
+    pub fn get(&self, i: uint, j: uint) -> T {
+        for p in range(self.row_ptr[i], self.row_ptr[i + 1]) {
+            if self.col_idx[p] == j {
+                return self.values[p];
+            }
+        }
+        zero()
+    }
+
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut res = Matrix::zero(self.dimx, self.dimy);
+        for i in range(0, self.dimx) {
+            for p in range(self.row_ptr[i], self.row_ptr[i + 1]) {
+                res[(i, self.col_idx[p])] = self.values[p];
+            }
+        }
+        res
+    }
+
+    pub fn mul_dense(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        if self.dimy != other.dimx {
+            return Err(DimensionMismatch((self.dimy, other.dimy), (other.dimx, other.dimy)));
+        }
+        let mut res = Matrix::zero(self.dimx, other.dimy);
+        for i in range(0, self.dimx) {
+            for p in range(self.row_ptr[i], self.row_ptr[i + 1]) {
+                for c in range(0, other.dimy) {
+                    res[(i, c)] = res[(i, c)] + self.values[p] * other[(self.col_idx[p], c)];
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    // Elimination order that keeps the cholesky factor sparse: repeatedly eliminate the
+    // variable with the fewest neighbours, connecting its neighbours to each other.
+    pub fn minimum_degree(&self) -> Vec<uint> {
+        let n = self.dimx;
+        let mut adj: Vec<HashSet<uint>> = Vec::from_fn(n, |i| {
+            self.row(i).iter().map(|&(j, _)| j).filter(|&j| j != i).collect()
+        });
+        let mut eliminated = Vec::from_elem(n, false);
+        let mut queue = PriorityQueue::new();
+        for i in range(0, n) {
+            queue.push(Candidate { degree: adj[i].len(), node: i });
+        }
+
+        let mut order = Vec::with_capacity(n);
+        loop {
+            let c = match queue.pop() {
+                None => break,
+                Some(c) => c,
+            };
+            // stale entry, the degree changed since it was queued
+            if eliminated[c.node] || c.degree != adj[c.node].len() {
+                continue;
+            }
+
+            let v = c.node;
+            let nbrs: Vec<uint> = adj[v].iter().map(|&j| j).collect();
+            for &a in nbrs.iter() {
+                adj.get_mut(a).remove(&v);
+                for &b in nbrs.iter() {
+                    if a != b {
+                        adj.get_mut(a).insert(b);
+                    }
+                }
+            }
+            for &a in nbrs.iter() {
+                queue.push(Candidate { degree: adj[a].len(), node: a });
+            }
+            adj.get_mut(v).clear();
+            *eliminated.get_mut(v) = true;
+            order.push(v);
+        }
+        order
+    }
+
+    // P A trans(P) = L trans(L) for symmetric positive definite A, in minimum degree order
+    pub fn cholesky(&self) -> Result<SparseCholesky<T>, MatrixError> {
+        if self.dimx != self.dimy {
+            return Err(DimensionMismatch((self.dimx, self.dimx), (self.dimx, self.dimy)));
+        }
+        let n = self.dimx;
+        let perm = self.minimum_degree();
+        let mut pinv = Vec::from_elem(n, 0u);
+        for k in range(0, n) {
+            *pinv.get_mut(perm[k]) = k;
+        }
+
+        // upper triangle of the permuted matrix, by column
+        let upper: Vec<Vec<(uint, T)>> = Vec::from_fn(n, |k| {
+            self.row(perm[k]).iter()
+                .map(|&(j, v)| (pinv[j], v))
+                .filter(|&(i, _)| i <= k)
+                .collect()
+        });
+
+        // elimination tree
+        let mut parent: Vec<Option<uint>> = Vec::from_elem(n, None);
+        let mut ancestor: Vec<Option<uint>> = Vec::from_elem(n, None);
+        for k in range(0, n) {
+            for &(i, _) in upper[k].iter() {
+                let mut i = Some(i);
+                loop {
+                    match i {
+                        Some(j) if j < k => {
+                            let next = ancestor[j];
+                            *ancestor.get_mut(j) = Some(k);
+                            if next.is_none() {
+                                *parent.get_mut(j) = Some(k);
+                            }
+                            i = next;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+
+        // up-looking: row k of L from a sparse triangular solve against rows 0..k
+        let mut l: Vec<Vec<(uint, T)>> = Vec::from_elem(n, Vec::new());
+        let mut x = Vec::from_elem(n, zero::<T>());
+        let mut flag = Vec::from_elem(n, n);
+        for k in range(0, n) {
+            // nonzero pattern of row k: paths up the tree from the entries of column k
+            *flag.get_mut(k) = k;
+            let mut paths = Vec::new();
+            for &(i, v) in upper[k].iter() {
+                *x.get_mut(i) = x[i] + v;
+                let mut path = Vec::new();
+                let mut i = i;
+                while flag[i] != k {
+                    path.push(i);
+                    *flag.get_mut(i) = k;
+                    i = parent[i].unwrap();
+                }
+                paths.push(path);
+            }
+
+            let mut d = x[k];
+            *x.get_mut(k) = zero();
+            // later paths hang below earlier ones, so they go first
+            for path in paths.iter().rev() {
+                for &i in path.iter() {
+                    let lki = x[i] / l[i][0].1;
+                    *x.get_mut(i) = zero();
+                    for p in range(1, l[i].len()) {
+                        let (r, v) = l[i][p];
+                        *x.get_mut(r) = x[r] - v * lki;
+                    }
+                    d = d - lki * lki;
+                    l.get_mut(i).push((k, lki));
+                }
+            }
+            // also catches a NaN pivot
+            if !(d > zero()) {
+                return Err(NotPositiveDefinite(perm[k]));
+            }
+            l.get_mut(k).insert(0, (k, d.sqrt()));
+        }
+
+        Ok(SparseCholesky {
+            perm: perm,
+            l: l,
+        })
+    }
+
+    // x such that self * x = b, for symmetric positive definite self
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let chol = try!(self.cholesky());
+        chol.solve(b)
+    }
+}
+
+
+// Column k of L starts with the diagonal (k, L[k][k]), then the entries below it.
+#[deriving(Show,Clone)]
+pub struct SparseCholesky<T> {
+    perm: Vec<uint>,
+    l: Vec<Vec<(uint, T)>>,
+}
+
+
+impl<T: Float> SparseCholesky<T> {
+    pub fn nnz(&self) -> uint {
+        self.l.iter().map(|col| col.len()).fold(0, |a, b| a + b)
+    }
+
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        let n = self.l.len();
+        if b.dimx != n {
+            return Err(DimensionMismatch((n, b.dimy), (b.dimx, b.dimy)));
+        }
+
+        let mut res = Matrix::zero(n, b.dimy);
+        for c in range(0, b.dimy) {
+            let mut y = Vec::from_fn(n, |k| b[(self.perm[k], c)]);
+
+            // L y' = P b
+            for j in range(0, n) {
+                *y.get_mut(j) = y[j] / self.l[j][0].1;
+                for p in range(1, self.l[j].len()) {
+                    let (r, v) = self.l[j][p];
+                    *y.get_mut(r) = y[r] - v * y[j];
+                }
+            }
+            // trans(L) z = y'
+            for j_ in range(0, n) {
+                let j = n - (j_ + 1);
+                for p in range(1, self.l[j].len()) {
+                    let (r, v) = self.l[j][p];
+                    *y.get_mut(j) = y[j] - v * y[r];
+                }
+                *y.get_mut(j) = y[j] / self.l[j][0].1;
+            }
+
+            for k in range(0, n) {
+                res[(self.perm[k], c)] = y[k];
+            }
+        }
+        Ok(res)
+    }
+}
+
+
+// min-heap entry for the minimum degree ordering
+#[deriving(PartialEq,Eq)]
+struct Candidate {
+    degree: uint,
+    node: uint,
+}
+
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        (other.degree, other.node).cmp(&(self.degree, self.node))
+    }
+}
@@ -0,0 +1,121 @@
+use std::cmp::min;
+use std::num::{Float, zero, one};
+use matrix::{Matrix, lit, dot, rotate_rows, descending};
+
+
+static MAX_SWEEPS: uint = 50;
+
+
+// a = u diag(s) trans(v), by one-sided jacobi rotations of the columns of a. Thin: for
+// an m x n matrix u is m x k and v is n x k with k = min(m, n). Singular values are
+// sorted largest first; columns of u that belong to a zero singular value are zero.
+#[deriving(Show,Clone)]
+pub struct Svd<T> {
+    u: Matrix<T>,
+    s: Vec<T>,
+    v: Matrix<T>,
+}
+
+
+impl<T: Float> Svd<T> {
+    pub fn new(a: &Matrix<T>) -> Svd<T> {
+        if a.dimx < a.dimy {
+            // a = trans(trans(a)) = v s trans(u)
+            let t = Svd::new(&a.transpose());
+            return Svd { u: t.v, s: t.s, v: t.u };
+        }
+        let m = a.dimx;
+        let n = a.dimy;
+
+        // rows of w are the columns of a, rows of vt the columns of v
+        let mut w = a.transpose();
+        let mut vt = Matrix::identity(n);
+
+        for _ in range(0, MAX_SWEEPS) {
+            let mut rotated = false;
+            for p in range(0, n) {
+                for q in range(p+1, n) {
+                    let alpha = dot(w.row(p), w.row(p));
+                    let beta = dot(w.row(q), w.row(q));
+                    let gamma = dot(w.row(p), w.row(q));
+                    if gamma.abs() <= Float::epsilon() * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+
+                    // rotation that makes columns p and q orthogonal
+                    let zeta = (beta - alpha) / (lit::<T>(2.0) * gamma);
+                    let t = one::<T>() / (zeta.abs() + (zeta * zeta + one()).sqrt());
+                    let t = if zeta < zero() { -t } else { t };
+                    let c = one::<T>() / (t * t + one()).sqrt();
+                    let s = t * c;
+
+                    rotate_rows(&mut w, p, q, c, s);
+                    rotate_rows(&mut vt, p, q, c, s);
+                }
+            }
+            if !rotated {
+                break;
+            }
+        }
+
+        let norms: Vec<T> = range(0, n).map(|i| dot(w.row(i), w.row(i)).sqrt()).collect();
+        let mut order = Vec::from_fn(n, |i| i);
+        order.sort_by(|&i, &j| descending(&norms[i], &norms[j]));
+
+        let mut u = Matrix::zero(m, n);
+        let mut v = Matrix::zero(n, n);
+        for k in range(0, n) {
+            let j = order[k];
+            for i in range(0, m) {
+                u[(i, k)] = if norms[j] > zero() { w[(j, i)] / norms[j] } else { zero() };
+            }
+            for i in range(0, n) {
+                v[(i, k)] = vt[(j, i)];
+            }
+        }
+        Svd {
+            u: u,
+            s: order.iter().map(|&j| norms[j]).collect(),
+            v: v,
+        }
+    }
+
+    pub fn u(&self) -> Matrix<T> {
+        self.u.clone()
+    }
+
+    pub fn singular_values(&self) -> Vec<T> {
+        self.s.clone()
+    }
+
+    pub fn v(&self) -> Matrix<T> {
+        self.v.clone()
+    }
+
+    // largest over smallest singular value; infinite for a singular matrix
+    pub fn condition_number(&self) -> T {
+        let k = min(self.u.dimx, self.v.dimx);
+        if k == 0 {
+            return one();
+        }
+        self.s[0] / self.s[k - 1]
+    }
+
+    // singular values above tol
+    pub fn rank(&self, tol: T) -> uint {
+        self.s.iter().filter(|&&x| x > tol).count()
+    }
+}
+
+
+impl<T: Float> Matrix<T> {
+    pub fn svd(&self) -> Svd<T> {
+        Svd::new(self)
+    }
+
+    // 2-norm condition number, a quick check for a badly conditioned omega
+    pub fn condition_number(&self) -> T {
+        self.svd().condition_number()
+    }
+}
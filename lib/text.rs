@@ -0,0 +1,166 @@
+use std::cmp::max;
+use std::num::{Float, cast};
+use matrix::{Matrix, MatrixError, ParseError, lit};
+
+
+// digits after the point in `print`
+static PRINT_PRECISION: uint = 3;
+
+
+impl<T: Float> Matrix<T> {
+    // numpy-like layout, columns aligned:
+    // [[ 1.000 -2.000]
+    //  [ 3.000  4.000]]
+    pub fn to_text(&self, precision: uint) -> String {
+        let cells: Vec<Vec<String>> = Vec::from_fn(self.dimx, |i| {
+            self.row(i).iter().map(|&x| format!("{:.1$f}", to_f64(x), precision)).collect()
+        });
+        let width = cells.iter().flat_map(|row| row.iter()).map(|c| c.len()).fold(0, max);
+
+        let mut res = String::from_str("[");
+        for i in range(0, self.dimx) {
+            if i > 0 {
+                res.push_str("\n ");
+            }
+            res.push_str("[");
+            for j in range(0, self.dimy) {
+                if j > 0 {
+                    res.push_str(" ");
+                }
+                res.push_str(format!("{:>1$}", cells[i][j], width).as_slice());
+            }
+            res.push_str("]");
+        }
+        res.push_str("]");
+        res
+    }
+
+    // Reads the to_text layout, with or without commas (numpy's repr), or whitespace
+    // separated rows without brackets (numpy's savetxt). A single bracketed list is one row.
+    pub fn from_text(s: &str) -> Result<Matrix<T>, MatrixError> {
+        if !s.contains_char('[') {
+            return parse_lines(s, true, |line| line.split(is_separator));
+        }
+
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut depth = 0u;
+        let mut max_depth = 0u;
+        let mut number_depth = 0u; // depth of the numbers, once one is read
+        let mut token = String::new();
+        let mut line = 1u;
+        for c in s.chars() {
+            if c == '[' || c == ']' || is_separator(c) {
+                if token.len() > 0 {
+                    if depth == 0 {
+                        return Err(ParseError(line, format!("`{}` outside brackets", token)));
+                    }
+                    // numbers go in the innermost lists only, so "[[1 2] 3]" is an error
+                    if depth < max_depth || (number_depth > 0 && depth != number_depth) {
+                        return Err(ParseError(line, format!("`{}` next to a row", token)));
+                    }
+                    number_depth = depth;
+                    row.push(try!(parse_number(token.as_slice(), line)));
+                    token = String::new();
+                }
+            }
+            match c {
+                '[' => {
+                    depth += 1;
+                    if depth > 2 {
+                        return Err(ParseError(line, "nested too deeply".to_string()));
+                    }
+                    if number_depth > 0 && depth > number_depth {
+                        return Err(ParseError(line, "row next to a number".to_string()));
+                    }
+                    if depth > max_depth {
+                        max_depth = depth;
+                    }
+                }
+                ']' => {
+                    if depth == 0 {
+                        return Err(ParseError(line, "unmatched `]`".to_string()));
+                    }
+                    if depth == max_depth && (row.len() > 0 || max_depth == 2) {
+                        rows.push(row);
+                        row = Vec::new();
+                    }
+                    depth -= 1;
+                }
+                '\n' => line += 1,
+                c if is_separator(c) => (),
+                c => token.push(c),
+            }
+        }
+        if depth != 0 || token.len() > 0 {
+            return Err(ParseError(line, "unmatched `[`".to_string()));
+        }
+        Matrix::new(rows)
+    }
+
+    // one row per line, full precision so that from_csv reads back the same values
+    pub fn to_csv(&self) -> String {
+        let mut res = String::new();
+        for i in range(0, self.dimx) {
+            let cells: Vec<String> = self.row(i).iter().map(|&x| format!("{}", to_f64(x))).collect();
+            res.push_str(cells.connect(",").as_slice());
+            res.push_str("\n");
+        }
+        res
+    }
+
+    pub fn from_csv(s: &str) -> Result<Matrix<T>, MatrixError> {
+        parse_lines(s, false, |line| line.split(','))
+    }
+
+    pub fn print(&self, prefix: &str) {
+        for line in self.to_text(PRINT_PRECISION).as_slice().lines() {
+            println!("{}{}", prefix, line);
+        }
+        println!("");
+    }
+}
+
+
+fn is_separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+
+fn to_f64<T: Float>(x: T) -> f64 {
+    cast(x).unwrap()
+}
+
+
+fn parse_number<T: Float>(token: &str, line: uint) -> Result<T, MatrixError> {
+    match from_str::<f64>(token.trim()) {
+        Some(x) => Ok(lit(x)),
+        None => Err(ParseError(line, format!("`{}` is not a number", token.trim()))),
+    }
+}
+
+
+// Blank lines and lines starting with # are skipped. Empty tokens are skipped too when
+// skip_empty is set, as runs of separators give them; otherwise they are an error.
+fn parse_lines<'a, T: Float, I: Iterator<&'a str>>(s: &'a str, skip_empty: bool,
+        split: |&'a str| -> I) -> Result<Matrix<T>, MatrixError> {
+    let mut rows = Vec::new();
+    for (n, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.len() == 0 || line.starts_with("#") {
+            continue;
+        }
+        let mut row = Vec::new();
+        for token in split(line) {
+            if token.trim().len() == 0 {
+                if skip_empty {
+                    continue;
+                }
+                return Err(ParseError(n + 1, "empty cell".to_string()));
+            }
+            row.push(try!(parse_number(token, n + 1)));
+        }
+        rows.push(row);
+    }
+    Matrix::new(rows)
+}
@@ -0,0 +1,60 @@
+extern crate cs373;
+
+use cs373::matrix::Matrix;
+use cs373::kalman::LinearModel;
+use cs373::consistency::{Verdict, evaluate};
+
+
+static NUM_RUNS: uint = 50;
+static NUM_STEPS: uint = 100;
+static ALPHA: f64 = 0.05;
+static SEED: uint = 373;
+
+
+// 1-D constant velocity with white noise acceleration of spectral density `accel_noise`
+fn constant_velocity(dt: f32, accel_noise: f32, measurement_noise: f32) -> LinearModel {
+    let a = Matrix::new(vec![vec![0.0, 1.0],
+                             vec![0.0, 0.0]]).unwrap();
+    let l = Matrix::new(vec![vec![0.0],
+                             vec![1.0]]).unwrap();
+    let h = Matrix::new(vec![vec![1.0, 0.0]]).unwrap();
+    let r = Matrix::new(vec![vec![measurement_noise]]).unwrap();
+    LinearModel::from_continuous(&a, &l, &Matrix::new(vec![vec![accel_noise]]).unwrap(), &h, &r, dt)
+}
+
+
+fn print(verdict: &Verdict) {
+    println!("NEES  = {:.3f}  bounds [{:.3f}, {:.3f}]  steps inside {:.3f}  {}",
+             verdict.final_nees(), verdict.nees_bounds.lower, verdict.nees_bounds.upper,
+             verdict.nees_bounds.fraction_inside(&verdict.nees),
+             if verdict.nees_consistent() { "ok" } else { "INCONSISTENT" });
+    println!("ANIS  = {:.3f}  bounds [{:.3f}, {:.3f}]  steps inside {:.3f}  {}",
+             verdict.average_nis, verdict.average_nis_bounds.lower, verdict.average_nis_bounds.upper,
+             verdict.nis_bounds.fraction_inside(&verdict.nis),
+             if verdict.nis_consistent() { "ok" } else { "INCONSISTENT" });
+    println!("verdict: {}", if verdict.passed() { "PASS" } else { "FAIL" });
+}
+
+
+fn initial() -> (Matrix<f32>, Matrix<f32>) {
+    let x0 = Matrix::new(vec![vec![0.0], vec![1.0]]).unwrap();
+    let p0 = Matrix::new(vec![vec![1.0, 0.0],
+                              vec![0.0, 1.0]]).unwrap();
+    (x0, p0)
+}
+
+
+fn main() {
+    let truth = constant_velocity(0.1, 1.0, 0.1);
+    let (x0, p0) = initial();
+
+    println!("=matched filter=");
+    print(&evaluate(&truth, &truth, &x0, &p0, NUM_RUNS, NUM_STEPS, ALPHA, SEED));
+
+    // a filter that trusts its measurements ten times too much is overconfident
+    println!("\n=overconfident filter=");
+    let mut mistuned = truth.clone();
+    mistuned.r = truth.r.scale(0.1);
+    print(&evaluate(&truth, &mistuned, &x0, &p0, NUM_RUNS, NUM_STEPS, ALPHA, SEED));
+}
+
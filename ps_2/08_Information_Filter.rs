@@ -0,0 +1,215 @@
+extern crate cs373;
+
+use cs373::matrix::{Matrix, MatrixError, Singular};
+use cs373::kalman::{LinearModel, KalmanFilter};
+
+
+fn model(dt: f32) -> LinearModel {
+    // constant velocity in x and y
+    let a = Matrix::new(vec![vec![0.0, 0.0, 1.0, 0.0],
+                             vec![0.0, 0.0, 0.0, 1.0],
+                             vec![0.0, 0.0, 0.0, 0.0],
+                             vec![0.0, 0.0, 0.0, 0.0]]).unwrap();
+
+    // noise enters as acceleration
+    let l = Matrix::new(vec![vec![0.0, 0.0],
+                             vec![0.0, 0.0],
+                             vec![1.0, 0.0],
+                             vec![0.0, 1.0]]).unwrap();
+
+    // noise free motion
+    let qc = Matrix::zero(2, 2);
+
+    let h = Matrix::new(vec![vec![1.0, 0.0, 0.0, 0.0],
+                             vec![0.0, 1.0, 0.0, 0.0]]).unwrap();
+
+    let r = Matrix::new(vec![vec![0.1, 0.0],
+                             vec![0.0, 0.1]]).unwrap();
+
+    LinearModel::from_continuous(&a, &l, &qc, &h, &r, dt)
+}
+
+
+// Canonical (information) form of the gaussian: omega = inv(p), xi = inv(p) * x.
+// A prior that knows nothing is simply omega = 0, which the covariance form can't express.
+#[deriving(Show,Clone)]
+struct InformationFilter {
+    omega: Matrix<f32>, // information matrix
+    xi: Matrix<f32>,    // information vector
+}
+
+
+impl InformationFilter {
+    fn new(x: &Matrix<f32>, p: &Matrix<f32>) -> InformationFilter {
+        let omega = p.inverse();
+        InformationFilter {
+            xi: omega * *x,
+            omega: omega,
+        }
+    }
+
+    fn zero_information(dim: uint) -> InformationFilter {
+        InformationFilter {
+            omega: Matrix::zero(dim, dim),
+            xi: Matrix::zero(dim, 1),
+        }
+    }
+
+    fn predict(&mut self, cxt: &LinearModel) -> Result<(), MatrixError> {
+        let f_inv = match cxt.f_inv {
+            Some(ref f_inv) => f_inv,
+            None => return Err(Singular),
+        };
+        // m = trans(inv(f)) * omega * inv(f)
+        let f_inv_t = f_inv.transpose();
+        let m = f_inv_t * self.omega * *f_inv;
+        let xi = f_inv_t * self.xi;
+
+        // With process noise omega' = inv(inv(m) + q) = inv(i + m * q) * m, and x is
+        // unchanged so xi' = inv(i + m * q) * xi. i + m * q can be inverted even when m
+        // or q can't, e.g. for no prior information or noise in fewer dims than the state.
+        let a = cxt.i + m * cxt.q;
+        let omega = try!(a.solve(&m));
+        let xi = try!(a.solve(&xi));
+
+        // x' = f * x + u
+        self.xi = xi + omega * cxt.u;
+        self.omega = omega;
+        Ok(())
+    }
+
+    fn update(&mut self, cxt: &LinearModel, z: &Matrix<f32>) {
+        let h_t_r_inv = cxt.h.transpose() * cxt.r.inverse();
+        self.omega = self.omega + h_t_r_inv * cxt.h; // omega := omega + trans(h) * inv(r) * h
+        self.xi = self.xi + h_t_r_inv * *z;          // xi := xi + trans(h) * inv(r) * z
+    }
+
+    // covariance form (x, p); only defined once omega has full rank
+    fn state(&self) -> Result<(Matrix<f32>, Matrix<f32>), MatrixError> {
+        let p = try!(self.omega.checked_inverse());
+        Ok((p * self.xi, p))
+    }
+}
+
+
+fn main() {
+    let cxt = model(0.1);
+
+    let measurements: Vec<Vec<f32>> = vec![vec![5.0, 10.0],
+                                           vec![6.0, 8.0],
+                                           vec![7.0, 6.0],
+                                           vec![8.0, 4.0],
+                                           vec![9.0, 2.0],
+                                           vec![10.0, 0.0]];
+
+    // initial state
+    let x = Matrix::new(vec![vec![4.0],
+                             vec![12.0],
+                             vec![0.0],
+                             vec![0.0]]).unwrap();
+
+    // initial uncertainty
+    let p = Matrix::new(vec![vec![1.0, 0.0, 0.0, 0.0],
+                             vec![0.0, 1.0, 0.0, 0.0],
+                             vec![0.0, 0.0, 1000.0, 0.0],
+                             vec![0.0, 0.0, 0.0, 1000.0]]).unwrap();
+
+    let mut kf = KalmanFilter::new(&x, &p);
+    kf.run(&cxt, &measurements);
+    println!("kalman filter");
+    kf.x.print("x= ");
+    kf.p.print("p= ");
+
+    let filter = information_filter(&cxt, &measurements, InformationFilter::new(&x, &p));
+    let (x_if, p_if) = filter.and_then(|f| f.state()).unwrap();
+    println!("information filter");
+    x_if.print("x= ");
+    p_if.print("p= ");
+
+    let filter = information_filter(&cxt, &measurements, InformationFilter::zero_information(4));
+    let (x_0, p_0) = filter.and_then(|f| f.state()).unwrap();
+    println!("information filter, no prior");
+    x_0.print("x= ");
+    p_0.print("p= ");
+}
+
+
+fn information_filter(cxt: &LinearModel, measurements: &Vec<Vec<f32>>,
+        filter: InformationFilter) -> Result<InformationFilter, MatrixError> {
+    let mut filter = filter;
+    for n in range(0, measurements.len()) {
+        try!(filter.predict(cxt));
+        filter.update(cxt, &Matrix::new(vec![measurements[n].clone()]).unwrap().transpose());
+    }
+    Ok(filter)
+}
+
+
+#[test]
+fn matches_covariance_form() {
+    let a = Matrix::new(vec![vec![0.0, 0.0, 1.0, 0.0],
+                             vec![0.0, 0.0, 0.0, 1.0],
+                             vec![0.0, 0.0, 0.0, 0.0],
+                             vec![0.0, 0.0, 0.0, 0.0]]).unwrap();
+    let l = Matrix::new(vec![vec![0.0, 0.0],
+                             vec![0.0, 0.0],
+                             vec![1.0, 0.0],
+                             vec![0.0, 1.0]]).unwrap();
+    let h = Matrix::new(vec![vec![1.0, 0.0, 0.0, 0.0],
+                             vec![0.0, 1.0, 0.0, 0.0]]).unwrap();
+    let r = Matrix::new(vec![vec![0.1, 0.0],
+                             vec![0.0, 0.1]]).unwrap();
+
+    let measurements: Vec<Vec<f32>> = vec![vec![5.0, 10.0],
+                                           vec![6.0, 8.0],
+                                           vec![7.1, 6.0],
+                                           vec![8.0, 3.9],
+                                           vec![9.0, 2.0]];
+    let x = Matrix::new(vec![vec![4.0], vec![12.0], vec![0.0], vec![0.0]]).unwrap();
+    let p = Matrix::new(vec![vec![1.0, 0.0, 0.0, 0.0],
+                             vec![0.0, 1.0, 0.0, 0.0],
+                             vec![0.0, 0.0, 100.0, 0.0],
+                             vec![0.0, 0.0, 0.0, 100.0]]).unwrap();
+
+    // with and without process noise
+    for &qc in [0.0f32, 0.5].iter() {
+        let cxt = LinearModel::from_continuous(&a, &l, &Matrix::identity(2).scale(qc), &h, &r, 0.1);
+        assert_same(&cxt, &measurements, &x, &p);
+    }
+
+    // noise in x only leaves q singular
+    let l_x = Matrix::new(vec![vec![0.0], vec![0.0], vec![1.0], vec![0.0]]).unwrap();
+    let cxt = LinearModel::from_continuous(&a, &l_x, &Matrix::new(vec![vec![0.5]]).unwrap(),
+                                           &h, &r, 0.1);
+    assert!(cxt.q.determinant().unwrap().abs() < 1e-12);
+    assert_same(&cxt, &measurements, &x, &p);
+}
+
+
+#[cfg(test)]
+fn assert_same(cxt: &LinearModel, measurements: &Vec<Vec<f32>>, x: &Matrix<f32>, p: &Matrix<f32>) {
+    let mut kf = KalmanFilter::new(x, p);
+    kf.run(cxt, measurements);
+    let filter = information_filter(cxt, measurements, InformationFilter::new(x, p));
+    let (x_if, p_if) = filter.and_then(|f| f.state()).unwrap();
+    assert!(kf.x.max_abs_diff(&x_if) < 1e-2);
+    assert!(kf.p.max_abs_diff(&p_if) < 1e-2);
+}
+
+
+#[test]
+fn zero_information_prior() {
+    let cxt = model(0.1);
+
+    // exact constant velocity track: starts at (5, 10) moving at (10, -20)
+    let measurements = Vec::from_fn(6, |n| {
+        vec![5.0 + n as f32, 10.0 - 2.0 * n as f32]
+    });
+
+    let filter = InformationFilter::zero_information(4);
+    assert!(filter.omega.is_zero());
+
+    let (x, _) = information_filter(&cxt, &measurements, filter).and_then(|f| f.state()).unwrap();
+    let expected = Matrix::new(vec![vec![10.0], vec![0.0], vec![10.0], vec![-20.0]]).unwrap();
+    assert!(x.max_abs_diff(&expected) < 1e-2);
+}
@@ -1,7 +1,10 @@
-use std::rand::{random, task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::seed;
 
 #[deriving(Show,Clone)]
 struct Robot {
@@ -26,19 +29,19 @@ fn gaussian<F: Float>(mean: F, covar2: F, x: F) -> F {
     ((x - mean).powi(2) / -(covar2 + covar2)).exp() / (two_pi * covar2).sqrt()
 }
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
 
 impl Robot {
-    fn new(world_size: f32, length: f32) -> Robot {
+    fn new<R: Rng>(world_size: f32, length: f32, rng: &mut R) -> Robot {
         Robot {
             world_size: world_size,
-            x: random::<f32>() * world_size,
-            y: random::<f32>() * world_size,
-            orientation: random::<f32>() * Float::two_pi(),
+            x: rng.gen::<f32>() * world_size,
+            y: rng.gen::<f32>() * world_size,
+            orientation: rng.gen::<f32>() * Float::two_pi(),
             forward_noise: 0.0,
             turn_noise: 0.0,
             sense_noise: 0.0,
@@ -63,7 +66,7 @@ impl Robot {
         self.sense_noise = new_s_noise;
     }
 
-    fn travel(&self, motion : (f32, f32)) -> Robot {
+    fn travel<R: Rng>(&self, motion : (f32, f32), rng: &mut R) -> Robot {
         let (alpha, d) = motion;
         let beta = (d / self.length) * alpha.tan();
         let mut x = self.x;
@@ -83,7 +86,7 @@ impl Robot {
             y = y + d * theta.sin();
         }
 
-        let mut res = Robot::new(self.world_size, self.length);
+        let mut res = Robot::new(self.world_size, self.length, rng);
         res.set(x, y, theta);
         res.set_noise(self.forward_noise, self.turn_noise, self.sense_noise);
         res
@@ -91,13 +94,14 @@ impl Robot {
 }
 
 fn main() {
+    let mut rng = seed::from_env();
     let world_size: f32 = 100.0;
     let length: f32 = 20.0;
     let bearing_noise = 0.0;
     let steering_noise = 0.0;
     let distance_noise = 0.0;
 
-    let mut myrobot = Robot::new(world_size, length);
+    let mut myrobot = Robot::new(world_size, length, &mut rng);
     myrobot.set(0.0, 0.0, 0.0);
     myrobot.set_noise(bearing_noise, steering_noise, distance_noise);
 
@@ -105,7 +109,7 @@ fn main() {
 
     println!("{}", myrobot);
     for t in range(0,motions.len()) {
-        myrobot = myrobot.travel(motions[t]);
+        myrobot = myrobot.travel(motions[t], &mut rng);
         println!("{}", myrobot);
     }
 }
@@ -1,7 +1,10 @@
-use std::rand::{random, task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::seed;
 
 #[deriving(Show,Clone)]
 struct Robot {
@@ -21,19 +24,19 @@ fn modulo(n: f32, m: f32) -> f32 {
     k
 }
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
 
 impl Robot {
-    fn new(world_size: f32, length: f32) -> Robot {
+    fn new<R: Rng>(world_size: f32, length: f32, rng: &mut R) -> Robot {
         Robot {
             world_size: world_size,
-            x: random::<f32>() * world_size,
-            y: random::<f32>() * world_size,
-            orientation: random::<f32>() * Float::two_pi(),
+            x: rng.gen::<f32>() * world_size,
+            y: rng.gen::<f32>() * world_size,
+            orientation: rng.gen::<f32>() * Float::two_pi(),
             forward_noise: 0.0,
             turn_noise: 0.0,
             sense_noise: 0.0,
@@ -56,13 +59,13 @@ impl Robot {
         self.sense_noise = new_s_noise;
     }
 
-    fn sense(&self, landmarks: &Vec<(f32,f32)>) -> Vec<f32> {
+    fn sense<R: Rng>(&self, landmarks: &Vec<(f32,f32)>, rng: &mut R) -> Vec<f32> {
         let mut z: Vec<f32> = Vec::with_capacity(landmarks.len());
         for &(y,x) in landmarks.iter() {
             // sensed bearing
             let mut bearing = modulo((y - self.y).atan2(x - self.x) - self.orientation, Float::two_pi());
             // add error
-            bearing += gauss(0.0, self.sense_noise);
+            bearing += gauss(0.0, self.sense_noise, rng);
             bearing = modulo(bearing, Float::two_pi());
             z.push(bearing);
         }
@@ -71,6 +74,7 @@ impl Robot {
 }
 
 fn main() {
+    let mut rng = seed::from_env();
     let landmarks: Vec<(f32,f32)> = vec![(0.0, 100.0),
                                          (0.0, 0.0),
                                          (100.0, 0.0),
@@ -83,9 +87,9 @@ fn main() {
     let steering_noise = 0.0;
     let distance_noise = 0.0;
 
-    let mut myrobot = Robot::new(world_size, length);
+    let mut myrobot = Robot::new(world_size, length, &mut rng);
     myrobot.set(30.0, 20.0, 0.0);
     myrobot.set_noise(bearing_noise, steering_noise, distance_noise);
 
-    println!("{}\n{}", myrobot, myrobot.sense(&landmarks));
+    println!("{}\n{}", myrobot, myrobot.sense(&landmarks, &mut rng));
 }
@@ -1,8 +1,18 @@
 extern crate core;
+extern crate cs373;
 
-use std::rand::{random, task_rng};
+use std::cmp::Equal;
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath, FromPrimitive, abs};
+use cs373::pose::{Pose, HasPose, SetPose};
+use cs373::particle::{ParticleFilter, MotionModel, MeasurementModel};
+use cs373::particle::{Engine, Bootstrap, Regularize, Auxiliary};
+use cs373::models::BearingModel;
+use cs373::cluster::cluster;
+use cs373::association::{Unlabelled, MaximumLikelihood, Probabilistic};
+use cs373::regularize::Regularized;
+use cs373::seed;
 
 // static MAX_STEERING_ANGLE: f32 = core::f32::const::FRAC_PI_4;
 static LENGTH: f32 = 20.0;
@@ -34,23 +44,18 @@ fn modulo(n: f32, m: f32) -> f32 {
     k
 }
 
-fn gaussian<F: Float>(mean: F, covar2: F, x: F) -> F {
-    let two_pi: F = Float::two_pi();
-    ((x - mean).powi(2) / -(covar2 + covar2)).exp() / (two_pi * covar2).sqrt()
-}
-
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
 
 impl Robot {
-    fn new() -> Robot {
+    fn new<R: Rng>(rng: &mut R) -> Robot {
         Robot {
-            x: random::<f32>() * WORLD_SIZE,
-            y: random::<f32>() * WORLD_SIZE,
-            orientation: random::<f32>() * Float::two_pi(),
+            x: rng.gen::<f32>() * WORLD_SIZE,
+            y: rng.gen::<f32>() * WORLD_SIZE,
+            orientation: rng.gen::<f32>() * Float::two_pi(),
             bearing_noise: 0.0,
             steering_noise: 0.0,
             distance_noise: 0.0,
@@ -71,22 +76,11 @@ impl Robot {
         self.distance_noise = new_d_noise;
     }
 
-    fn measurement_prob(&self, measurements: &Vec<f32>) -> f32 {
-        let predicted_measurements: Vec<f32> = self.sense_without_noise();
-        let mut error: f32 = 1.0;
-        for i in range(0,measurements.len()) {
-            let mut error_bearing: f32 = abs(measurements[i] - predicted_measurements[i]);
-            error_bearing = modulo(error_bearing + Float::pi(), Float::two_pi()) - Float::pi();
-            error *= gaussian(error_bearing, self.bearing_noise, 0.0);
-        }
-        error
-    }
-
-    fn sense(&self) -> Vec<f32> {
+    fn sense<R: Rng>(&self, rng: &mut R) -> Vec<f32> {
         let mut z: Vec<f32> = Vec::with_capacity(LANDMARKS.len());
         for &(y,x) in LANDMARKS.iter() {
             // noise
-            let n = gauss(0.0, self.bearing_noise);
+            let n = gauss(0.0, self.bearing_noise, rng);
             // sensed bearing
             let bearing = modulo((y - self.y).atan2(x - self.x) - self.orientation + n, Float::two_pi());
             z.push(bearing);
@@ -94,19 +88,9 @@ impl Robot {
         z
     }
     
-    fn sense_without_noise(&self) -> Vec<f32> {
-        let mut z: Vec<f32> = Vec::with_capacity(LANDMARKS.len());
-        for &(y,x) in LANDMARKS.iter() {
-            // sensed bearing
-            let bearing = modulo((y - self.y).atan2(x - self.x) - self.orientation, Float::two_pi());
-            z.push(bearing);
-        }
-        z
-    }
-    
-    fn travel(&self, (alpha, d) : (f32, f32)) -> Robot {
-        let alpha = alpha + gauss(0.0, self.steering_noise);
-        let d = d + gauss(0.0, self.distance_noise);
+    fn travel<R: Rng>(&self, (alpha, d) : (f32, f32), rng: &mut R) -> Robot {
+        let alpha = alpha + gauss(0.0, self.steering_noise, rng);
+        let d = d + gauss(0.0, self.distance_noise, rng);
         let beta = (d / self.length) * alpha.tan();
         let mut x = self.x;
         let mut y = self.y;
@@ -125,7 +109,7 @@ impl Robot {
             y = y + d * theta.sin();
         }
 
-        let mut res = Robot::new();
+        let mut res = Robot::new(rng);
         res.set(x, y, theta);
         res.set_noise(self.bearing_noise, self.steering_noise, self.distance_noise);
         res
@@ -133,15 +117,59 @@ impl Robot {
 }
 
 
+impl HasPose for Robot {
+    fn pose(&self) -> Pose {
+        Pose::new(self.x, self.y, self.orientation)
+    }
+}
+
+
+impl SetPose for Robot {
+    fn set_pose(&mut self, pose: &Pose) {
+        self.set(pose.x, pose.y, pose.orientation);
+    }
+}
+
+
+// With bearing_noise at 0.1 the bootstrap filter's particles pile up on a few duplicates
+// after resampling; the other two are meant to keep the cloud from depleting.
+fn engines() -> Vec<(&'static str, Engine)> {
+    vec![("Bootstrap", Bootstrap),
+         ("Regularized", Regularize(Regularized::new())),
+         ("Auxiliary", Auxiliary)]
+}
+
+
+// the filter after one step
+struct Report {
+    estimate: (f32, f32, f32),
+    unique: uint, // distinct particles; resampling without jitter depletes them
+    ess: f64,
+}
+
+
+// bicycle model: (steering angle, distance)
+struct Bicycle;
+
+
+impl MotionModel<Robot, (f32, f32)> for Bicycle {
+    fn sample<R: Rng>(&self, r: &Robot, &motion: &(f32, f32), rng: &mut R) -> Robot {
+        r.travel(motion, rng)
+    }
+}
+
 fn main() {
+    let mut rng = seed::from_env();
     println!("\n=test_case_1=");
-    test_case_1();
+    test_case_1(&mut rng);
     println!("\n=test_case_2=");
-    test_case_2();
+    test_case_2(&mut rng);
+    println!("\n=test_case_3=");
+    test_case_3(&mut rng);
 }
 
 
-fn test_case_1() {
+fn test_case_1<R: Rng>(rng: &mut R) {
     let motions: Vec<(f32,f32)> = Vec::from_elem(8, (2.0_f32 * Float::pi() / 10.0_f32, 20.0));
     let measurements: Vec<Vec<f32>> = vec![
         vec![4.746936, 3.859782, 3.045217, 2.045506],
@@ -154,53 +182,98 @@ fn test_case_1() {
         vec![5.717342, 4.736780, 3.909599, 2.342536]];
 
     println!("Actual:          (93.476, 75.186, 5.2664)");
-    println!("Particle filter: {}", particle_filter(&motions, &measurements, 500));
+    for &(name, ref engine) in engines().iter() {
+        let reports = particle_filter(&motions, &measurements, &bearing_sensor(), engine, 500, rng);
+        println!("{}: {}", name, reports[reports.len() - 1].estimate);
+        print_reports(&reports, None);
+    }
 }
 
 
-fn test_case_2() {
+fn test_case_2<R: Rng>(rng: &mut R) {
     let number_of_iterations = 6;
-    let motions: Vec<(f32,f32)> = Vec::from_elem(number_of_iterations, (2.0_f32 * Float::pi() / 20.0_32, 12.0));
-    let (final_robot, measurements) = generate_ground_truth(&motions);
-    let estimated_position = particle_filter(&motions, &measurements, 500);
+    let motions: Vec<(f32,f32)> = Vec::from_elem(number_of_iterations, (2.0_f32 * Float::pi() / 20.0_f32, 12.0));
+    let (truth, measurements) = generate_ground_truth(&motions, rng);
+    let final_robot = &truth[truth.len() - 1];
 
     println!("Ground truth:    ({}, {}, {})", final_robot.x, final_robot.y, final_robot.orientation);
-    println!("Particle filter: {}", estimated_position);
-    println!("Code check:      {}", check_output(&final_robot, estimated_position));
+    for &(name, ref engine) in engines().iter() {
+        let reports = particle_filter(&motions, &measurements, &bearing_sensor(), engine, 500, rng);
+        let estimated_position = reports[reports.len() - 1].estimate;
+        println!("{}: {} {}", name, estimated_position,
+                 check_output(final_robot, estimated_position));
+        print_reports(&reports, Some(&truth));
+    }
 }
 
 
-fn get_position(ps: &Vec<Robot>) -> (f32, f32, f32) {
-    let mut x = 0.0;
-    let mut y = 0.0;
-    let mut orientation = 0.0;
+// Like test_case_2, but the bearings come shuffled, so the filter has to work out which
+// landmark each one belongs to.
+fn test_case_3<R: Rng>(rng: &mut R) {
+    let motions: Vec<(f32,f32)> = Vec::from_elem(6, (2.0_f32 * Float::pi() / 20.0_f32, 12.0));
+    let (truth, mut measurements) = generate_ground_truth(&motions, rng);
+    let final_robot = &truth[truth.len() - 1];
+    for z in measurements.iter_mut() {
+        rng.shuffle(z.as_mut_slice());
+    }
+    println!("Ground truth:    ({}, {}, {})", final_robot.x, final_robot.y, final_robot.orientation);
+    for &association in [MaximumLikelihood, Probabilistic].iter() {
+        let sensor = Unlabelled::new(bearing_sensor(), association);
+        let reports = particle_filter(&motions, &measurements, &sensor, &Bootstrap, 500, rng);
+        let estimated_position = reports[reports.len() - 1].estimate;
+        println!("{}: {} {}", association, estimated_position,
+                 check_output(final_robot, estimated_position));
+        print_reports(&reports, Some(&truth));
+    }
+}
 
-    for &p in ps.iter() {
-        x += p.x;
-        y += p.y;
 
-        // Because angles are cyclic, the orientation needs adjustment.
-        orientation  += modulo((p.orientation - ps[0].orientation + Float::pi()), Float::two_pi())
-                        + ps[0].orientation
-                        - Float::pi();
+// per step: distinct particles, effective sample size, and the distance from the
+// estimate to the true position when there is one
+fn print_reports(reports: &Vec<Report>, truth: Option<&Vec<Robot>>) {
+    for t in range(0, reports.len()) {
+        let r = &reports[t];
+        let (x, y, _) = r.estimate;
+        let error = match truth {
+            Some(truth) => format!("  error {:6.2f}", truth[t].pose().distance_to((x, y))),
+            None => String::new(),
+        };
+        println!("    step {}: unique {:3}  ess {:5.1f}{}", t + 1, r.unique, r.ess, error);
     }
+}
 
-    let len = ps.len() as f32;
-    (x / len, y / len, orientation / len)
+
+// how many particles are not copies of another
+fn unique(ps: &Vec<Robot>) -> uint {
+    let mut poses: Vec<(f32, f32, f32)> = ps.iter().map(|r| (r.x, r.y, r.orientation)).collect();
+    poses.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
+    poses.dedup();
+    poses.len()
+}
+
+
+// The most weighted mode of the particles. The plain weighted mean lands between the
+// modes when the landmarks leave the pose ambiguous.
+fn get_position(ps: &Vec<Robot>, ws: &Vec<f64>) -> (f32, f32, f32) {
+    let best = &cluster(ps, ws, 5.0, 0.5)[0];
+    (best.pose.x, best.pose.y, best.pose.orientation)
 }
 
 
-fn generate_ground_truth(motions: &Vec<(f32,f32)>) -> (Robot, Vec<Vec<f32>>) {
-    let mut myrobot = Robot::new();
+// the true robot after each motion, and what it sensed there
+fn generate_ground_truth<R: Rng>(motions: &Vec<(f32,f32)>, rng: &mut R) -> (Vec<Robot>, Vec<Vec<f32>>) {
+    let mut myrobot = Robot::new(rng);
     myrobot.set_noise(BEARING_NOISE, STEERING_NOISE, DISTANCE_NOISE);
 
+    let mut truth = Vec::with_capacity(motions.len());
     let mut z = Vec::with_capacity(motions.len());
     for &m in motions.iter() {
-        myrobot = myrobot.travel(m);
-        z.push(myrobot.sense());
+        myrobot = myrobot.travel(m, rng);
+        z.push(myrobot.sense(rng));
+        truth.push(myrobot.clone());
     };
 
-    (myrobot, z)
+    (truth, z)
 }
 
 #[allow(dead_code)]
@@ -222,39 +295,29 @@ fn check_output(final_robot: &Robot, (est_x, est_y, est_or): (f32, f32, f32)) ->
 }
 
 
-fn particle_filter(motions: &Vec<(f32, f32)>, measurements: &Vec<Vec<f32>>, n: uint) -> (f32, f32, f32) {
-    let mut ps = Vec::from_fn(n, |_| {
-        let mut r = Robot::new();
+// LANDMARKS are (y, x)
+fn bearing_sensor() -> BearingModel {
+    BearingModel::new(LANDMARKS.iter().map(|&(y, x)| (x, y)).collect(), BEARING_NOISE)
+}
+
+
+fn particle_filter<R: Rng, M: MeasurementModel<Robot, Vec<f32>>>(motions: &Vec<(f32, f32)>,
+        measurements: &Vec<Vec<f32>>, sensor: &M, engine: &Engine, n: uint,
+        rng: &mut R) -> Vec<Report> {
+    let mut filter = ParticleFilter::new(Vec::from_fn(n, |_| {
+        let mut r = Robot::new(rng);
         r.set_noise(BEARING_NOISE, STEERING_NOISE, DISTANCE_NOISE);
         r
-    });
+    })).with_engine(engine.clone());
 
+    let mut reports = Vec::with_capacity(motions.len());
     for t in range(0, motions.len()) {
-        // motion update (prediction)
-        ps = ps.map_in_place(|p| p.travel(motions[t]) );
-
-        // measurement update
-        let mut ws = Vec::with_capacity(ps.len());
-        for p in ps.iter() {
-            ws.push(p.measurement_prob(&measurements[t]));
-        }
-
-        // resampling
-        let mut p3: Vec<Robot> = Vec::with_capacity(n);
-        let mut index = random::<uint>() % n;
-        let mut beta = 0.0;
-        let mut mw = 0.0;
-        for &w in ws.iter() { if w > mw { mw = w; } } 
-        for _ in range(0, n) {
-            beta += random::<f32>() * 2.0 * mw;
-            while beta > ws[index] {
-                beta -= ws[index];
-                index = (index + n + 1) % n;
-            }
-            p3.push(ps[index].clone());
-        }
-        ps = p3;
+        filter.step(&Bicycle, &motions[t], sensor, &measurements[t], rng);
+        reports.push(Report {
+            estimate: get_position(filter.particles(), filter.weights()),
+            unique: unique(filter.particles()),
+            ess: filter.effective_sample_size(),
+        });
     }
-
-    get_position(&ps)
+    reports
 }
@@ -1,9 +1,12 @@
 #![feature(tuple_indexing)]
 
-use std::rand::{task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::seed;
 
 
 struct Robot {
@@ -51,12 +54,13 @@ impl Robot {
     }
 
 
-    fn travel(&self, steering: f32, distance: f32) -> Robot {
-        self.travel_with_extra(steering, distance, 0.001, Float::frac_pi_4())
+    fn travel<R: Rng>(&self, steering: f32, distance: f32, rng: &mut R) -> Robot {
+        self.travel_with_extra(steering, distance, 0.001, Float::frac_pi_4(), rng)
     }
 
 
-    fn travel_with_extra(&self, steering: f32, distance: f32, tolerance: f32, max_steering_angle: f32) -> Robot {
+    fn travel_with_extra<R: Rng>(&self, steering: f32, distance: f32, tolerance: f32, max_steering_angle: f32,
+            rng: &mut R) -> Robot {
         let mut steering = if steering > max_steering_angle { max_steering_angle } else { steering };
         steering = if steering < -max_steering_angle { -max_steering_angle } else { steering };
         let distance = if distance < 0.0 { 0.0 } else { distance };
@@ -69,8 +73,8 @@ impl Robot {
         res.steering_drift = self.steering_drift;
 
         // apply noise
-        let mut steering2 = gauss(steering, self.steering_noise);
-        let distance2 = gauss(distance, self.distance_noise);
+        let mut steering2 = gauss(steering, self.steering_noise, rng);
+        let distance2 = gauss(distance, self.distance_noise, rng);
 
         // apply steering drift
         steering2 += self.steering_drift;
@@ -129,7 +133,8 @@ impl Robot {
 }
 
 
-fn run_with_extra((tau_p, tau_d, tau_i): (f32, f32, f32), radius: f32, print_flag: bool) -> f32 {
+fn run_with_extra<R: Rng>((tau_p, tau_d, tau_i): (f32, f32, f32), radius: f32, print_flag: bool,
+        rng: &mut R) -> f32 {
     let mut myrobot = Robot::new();
     myrobot.set(0.0, radius, Float::frac_pi_2());
     let speed = 1.0;
@@ -147,7 +152,7 @@ fn run_with_extra((tau_p, tau_d, tau_i): (f32, f32, f32), radius: f32, print_fla
 
         let steer = -tau_p * cte + -tau_d  * cte_d + -tau_i * cte_i;
 
-        myrobot = myrobot.travel(steer, speed);
+        myrobot = myrobot.travel(steer, speed, rng);
 
         if i >= n {
             err += cte.powi(2);
@@ -165,7 +170,8 @@ fn run_with_extra((tau_p, tau_d, tau_i): (f32, f32, f32), radius: f32, print_fla
 fn main() {
     let radius = 25.0;
     let params = (10.0, 15.0, 0.0);
-    let err = run_with_extra(params, radius, true);
+    let mut rng = seed::from_env();
+    let err = run_with_extra(params, radius, true, &mut rng);
     println!("Final parameters: {} \n -> {:.15f}", params, err);
 }
 
@@ -177,8 +183,8 @@ fn modulo(n: f32, m: f32) -> f32 {
 }
 
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
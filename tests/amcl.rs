@@ -0,0 +1,114 @@
+extern crate cs373;
+
+use std::num::Float;
+use std::rand::{Rng, SeedableRng, StdRng};
+use std::rand::distributions::{Normal, IndependentSample};
+use cs373::amcl::{Augmented, StateSampler, UniformPose};
+use cs373::particle::{ParticleFilter, MotionModel};
+use cs373::pose::Pose;
+use cs373::models::RangeModel;
+
+
+// stays put, up to some jitter
+struct Jitter;
+
+impl MotionModel<Pose, ()> for Jitter {
+    fn sample<R: Rng>(&self, p: &Pose, _: &(), rng: &mut R) -> Pose {
+        let noise = Normal::new(0.0, 1.0);
+        Pose::new(p.x + noise.ind_sample(rng) as f32, p.y + noise.ind_sample(rng) as f32,
+                  p.orientation)
+    }
+}
+
+
+fn mean_error(filter: &ParticleFilter<Pose>, truth: &Pose) -> f32 {
+    let (mut x, mut y) = (0.0, 0.0);
+    for (p, &w) in filter.particles().iter().zip(filter.weights().iter()) {
+        x += w as f32 * p.x;
+        y += w as f32 * p.y;
+    }
+    truth.distance_to((x, y))
+}
+
+
+#[test]
+fn injection_follows_the_likelihood_averages() {
+    let mut aug = Augmented::new(0.05, 0.5);
+    assert_eq!(aug.injection_probability(), 0.0);
+    for _ in range(0u, 20) {
+        aug.observe(-2.0);
+    }
+    assert_eq!(aug.injection_probability(), 0.0);
+    aug.observe(-50.0);
+    let p = aug.injection_probability();
+    assert!(p > 0.1 && p < 1.0);
+    for _ in range(0u, 20) {
+        aug.observe(-2.0);
+    }
+    assert_eq!(aug.injection_probability(), 0.0);
+}
+
+
+#[test]
+fn relocalizes_after_kidnapping() {
+    let mut rng: StdRng = SeedableRng::from_seed([373u].as_slice());
+    let landmarks = vec![(20.0, 20.0), (80.0, 80.0), (20.0, 80.0), (80.0, 20.0)];
+    let sensor = RangeModel::new(landmarks, 3.0);
+    let world = UniformPose::new((0.0, 0.0), (100.0, 100.0));
+    let mut aug = Augmented::new(0.05, 0.5);
+
+    let start = Pose::new(30.0, 30.0, 0.0);
+    let mut filter = ParticleFilter::new(Vec::from_fn(500, |_| world.sample(&mut rng)));
+    for _ in range(0u, 20) {
+        let z = sensor.expected(&start);
+        filter.step_augmented(&Jitter, &(), &sensor, &z, &mut aug, &world, &mut rng);
+    }
+    assert!(mean_error(&filter, &start) < 3.0);
+
+    let kidnapped = Pose::new(70.0, 70.0, 0.0);
+    let mut injected = 0;
+    for _ in range(0u, 25) {
+        let z = sensor.expected(&kidnapped);
+        injected += filter.step_augmented(&Jitter, &(), &sensor, &z, &mut aug, &world, &mut rng);
+    }
+    assert!(injected > 0);
+    assert!(mean_error(&filter, &kidnapped) < 5.0);
+}
+
+
+// averages of a filter that has been explaining its measurements well
+fn well_tracked() -> Augmented {
+    let mut aug = Augmented::new(0.05, 0.5);
+    for _ in range(0u, 20) {
+        aug.observe(-2.0);
+    }
+    aug
+}
+
+
+// a bad measurement alone does not inject: only a resample does
+#[test]
+fn injects_only_when_resampling() {
+    let mut rng: StdRng = SeedableRng::from_seed([373u].as_slice());
+    let landmarks = vec![(20.0, 20.0), (80.0, 80.0), (20.0, 80.0), (80.0, 20.0)];
+    let sensor = RangeModel::new(landmarks, 3.0);
+    let world = UniformPose::new((0.0, 0.0), (100.0, 100.0));
+    let z = sensor.expected(&Pose::new(30.0, 30.0, 0.0));
+
+    // all particles agree, so the weights stay equal however unlikely z is
+    let lost = Pose::new(40.0, 40.0, 0.0);
+    let mut filter = ParticleFilter::new(Vec::from_elem(100, lost.clone()));
+    let mut aug = well_tracked();
+    assert!(filter.update(&sensor, &z));
+    assert_eq!(filter.resample_augmented(&mut aug, &world, &mut rng), 0);
+    assert!(aug.injection_probability() > 0.1);
+    assert!(filter.particles().iter().all(|p| *p == lost));
+
+    // one particle takes all the weight, so it resamples and injects
+    let mut particles = Vec::from_elem(99, Pose::new(80.0, 40.0, 0.0));
+    particles.push(lost);
+    let mut filter = ParticleFilter::new(particles);
+    let mut aug = well_tracked();
+    assert!(filter.update(&sensor, &z));
+    assert!(filter.resample_augmented(&mut aug, &world, &mut rng) > 0);
+}
@@ -0,0 +1,98 @@
+extern crate cs373;
+
+use std::rand::Rng;
+use std::num::Float;
+use cs373::association::{Unlabelled, MaximumLikelihood, Probabilistic};
+use cs373::models::{BearingModel, RangeBearingModel, Detection};
+use cs373::particle::MeasurementModel;
+use cs373::pose::Pose;
+use cs373::seed::seeded;
+
+
+// no symmetry of the square, so a set of bearings picks out one pose
+fn landmarks() -> Vec<(f32, f32)> {
+    vec![(0.0, 100.0), (0.0, 0.0), (100.0, 0.0), (60.0, 90.0)]
+}
+
+
+#[test]
+fn maximum_likelihood_finds_the_labels() {
+    let sensor = BearingModel::new(landmarks(), 0.05);
+    let pose = Pose::new(30.0, 40.0, 1.0);
+    let z: Vec<f32> = sensor.expected(&pose).iter().map(|&b| b + 0.02).collect();
+    let shuffled = vec![z[2], z[0], z[3], z[1]];
+    let ml = Unlabelled::new(sensor.clone(), MaximumLikelihood);
+    assert_eq!(ml.associate(&pose, &shuffled), vec![Some(2), Some(0), Some(3), Some(1)]);
+    let labelled = sensor.log_likelihood(&pose, &z);
+    assert!((ml.log_likelihood(&pose, &shuffled) - labelled).abs() < 1e-6);
+}
+
+
+#[test]
+fn order_does_not_matter() {
+    let sensor = BearingModel::new(landmarks(), 0.1);
+    let pose = Pose::new(70.0, 20.0, 4.0);
+    let z = sensor.expected(&pose);
+    let reversed: Vec<f32> = z.iter().rev().map(|&b| b).collect();
+    for &association in [MaximumLikelihood, Probabilistic].iter() {
+        let model = Unlabelled::new(sensor.clone(), association);
+        let other = Pose::new(50.0, 50.0, 0.0);
+        assert!((model.log_likelihood(&other, &z) - model.log_likelihood(&other, &reversed)).abs()
+                < 1e-9);
+    }
+}
+
+
+// the true pose explains anonymous bearings better than poses anywhere else
+#[test]
+fn true_pose_wins_without_labels() {
+    let sensor = BearingModel::new(landmarks(), 0.05);
+    let truth = Pose::new(25.0, 65.0, 2.0);
+    let mut z = sensor.expected(&truth);
+    let mut rng = seeded(3);
+    rng.shuffle(z.as_mut_slice());
+    for &association in [MaximumLikelihood, Probabilistic].iter() {
+        let model = Unlabelled::new(sensor.clone(), association);
+        let here = model.log_likelihood(&truth, &z);
+        for _ in range(0u, 2000) {
+            let p = Pose::new(rng.gen::<f32>() * 100.0, rng.gen::<f32>() * 100.0,
+                              rng.gen::<f32>() * Float::two_pi());
+            if p.distance_to((truth.x, truth.y)) > 10.0 {
+                assert!(model.log_likelihood(&p, &z) < here);
+            }
+        }
+    }
+}
+
+
+#[test]
+fn stray_detections_are_left_as_clutter() {
+    let sensor = RangeBearingModel::new(landmarks(), 1.0, 0.05).with_limits(200.0, Float::two_pi())
+        .with_detection(0.9, 1.0);
+    let pose = Pose::new(10.0, 10.0, 0.0);
+    let (r, b) = sensor.expected(&pose, 1).unwrap();
+    let z = vec![Detection { range: 5.0, bearing: 2.0, landmark: None },
+                 Detection { range: r, bearing: b, landmark: None }];
+    let ml = Unlabelled::new(sensor.clone(), MaximumLikelihood);
+    assert_eq!(ml.associate(&pose, &z), vec![None, Some(1)]);
+    let pda = Unlabelled::new(sensor, Probabilistic);
+    assert!(pda.log_likelihood(&pose, &z).is_finite());
+}
+
+
+// sightings of each landmark and clutter, mixed in proportion to how often each occurs
+#[test]
+fn probabilistic_score_is_a_normalized_mixture() {
+    let fov: f32 = Float::two_pi();
+    let sensor = RangeBearingModel::new(landmarks(), 1.0, 0.05).with_limits(200.0, fov)
+        .with_detection(0.9, 1.0);
+    let pose = Pose::new(10.0, 10.0, 0.0);
+    let (r, b) = sensor.expected(&pose, 1).unwrap();
+    let z = vec![Detection { range: r + 0.5, bearing: b, landmark: None }];
+    let pc = 1.0 / (1.0 + 0.9f32 as f64 * 4.0);
+    let area = 200.0 * fov as f64;
+    let sightings = range(0u, 4).fold(0.0, |a, j| a + sensor.log_sighting(&pose, &z[0], j).exp());
+    let expected = ((1.0 - pc) / 4.0 * sightings + pc / area).ln();
+    let pda = Unlabelled::new(sensor, Probabilistic);
+    assert!((pda.log_likelihood(&pose, &z) - expected).abs() < 1e-6);
+}
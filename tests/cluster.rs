@@ -0,0 +1,48 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::cluster::cluster;
+use cs373::pose::Pose;
+
+
+#[test]
+fn bimodal_cloud_gives_two_ranked_modes() {
+    let mut ps = Vec::new();
+    let mut ws = Vec::new();
+    for i in range(0u, 10) {
+        ps.push(Pose::new(10.0 + i as f32 * 0.1, 20.0, 1.0));
+        ws.push(0.06);
+        ps.push(Pose::new(80.0, 60.0 + i as f32 * 0.1, 3.0));
+        ws.push(0.04);
+    }
+    let hs = cluster(&ps, &ws, 1.0, 0.5);
+    assert_eq!(hs.len(), 2);
+    assert!((hs[0].weight - 0.6).abs() < 1e-9);
+    assert!((hs[1].weight - 0.4).abs() < 1e-9);
+    assert!((hs[0].pose.x - 10.45).abs() < 1e-4 && (hs[0].pose.y - 20.0).abs() < 1e-4);
+    assert!((hs[1].pose.x - 80.0).abs() < 1e-4 && (hs[1].pose.y - 60.45).abs() < 1e-4);
+    // spread along x only in the first mode, along y only in the second
+    assert!(hs[0].covariance[(0, 0)] > 0.0 && hs[0].covariance[(1, 1)] < 1e-9);
+    assert!(hs[1].covariance[(1, 1)] > 0.0 && hs[1].covariance[(0, 0)] < 1e-9);
+}
+
+
+#[test]
+fn orientation_wraps_around() {
+    let two_pi: f32 = Float::two_pi();
+    let ps = vec![Pose::new(5.0, 5.0, 0.05), Pose::new(5.0, 5.0, two_pi - 0.05)];
+    let hs = cluster(&ps, &vec![0.5, 0.5], 1.0, 0.2);
+    assert_eq!(hs.len(), 1);
+    let o = hs[0].pose.orientation;
+    assert!(o < 1e-3 || two_pi - o < 1e-3);
+    assert!((hs[0].covariance[(2, 2)] - 0.0025).abs() < 1e-5);
+}
+
+
+// a NaN weight spoils the ranking but must not panic the sort
+#[test]
+fn nan_weight_does_not_panic() {
+    let ps = vec![Pose::new(10.0, 10.0, 1.0), Pose::new(80.0, 80.0, 1.0)];
+    let hs = cluster(&ps, &vec![Float::nan(), 0.5], 1.0, 0.5);
+    assert_eq!(hs.len(), 2);
+}
@@ -0,0 +1,73 @@
+extern crate cs373;
+
+use cs373::matrix::Matrix;
+use cs373::kalman::LinearModel;
+use cs373::consistency::evaluate;
+use cs373::stats::{normal_quantile, chi2_quantile};
+
+
+static RUNS: uint = 50;
+static STEPS: uint = 100;
+static ALPHA: f64 = 0.01;
+static SEED: uint = 373;
+
+
+// 1-D constant velocity, position measured
+fn constant_velocity() -> LinearModel {
+    let a = Matrix::new(vec![vec![0.0, 1.0],
+                             vec![0.0, 0.0]]).unwrap();
+    let l = Matrix::new(vec![vec![0.0],
+                             vec![1.0]]).unwrap();
+    let h = Matrix::new(vec![vec![1.0, 0.0]]).unwrap();
+    let r = Matrix::new(vec![vec![0.1]]).unwrap();
+    LinearModel::from_continuous(&a, &l, &Matrix::new(vec![vec![1.0]]).unwrap(), &h, &r, 0.1)
+}
+
+
+fn initial() -> (Matrix<f32>, Matrix<f32>) {
+    (Matrix::new(vec![vec![0.0], vec![1.0]]).unwrap(), Matrix::identity(2))
+}
+
+
+#[test]
+fn quantiles() {
+    assert!(normal_quantile(0.5).abs() < 1e-9);
+    assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-5);
+    assert!((normal_quantile(0.001) + 3.090232).abs() < 1e-5);
+    // chi-square with 10 dof: 3.940 and 18.307 are the 5% and 95% points
+    assert!((chi2_quantile(0.05, 10.0) - 3.940).abs() < 0.02);
+    assert!((chi2_quantile(0.95, 10.0) - 18.307).abs() < 0.05);
+}
+
+
+#[test]
+fn matched_filter_passes() {
+    let truth = constant_velocity();
+    let (x0, p0) = initial();
+    let verdict = evaluate(&truth, &truth, &x0, &p0, RUNS, STEPS, ALPHA, SEED);
+    assert!(verdict.passed());
+}
+
+
+#[test]
+fn understated_measurement_noise_fails() {
+    let truth = constant_velocity();
+    let (x0, p0) = initial();
+    let mut filter = truth.clone();
+    filter.r = truth.r.scale(0.1);
+    let verdict = evaluate(&truth, &filter, &x0, &p0, RUNS, STEPS, ALPHA, SEED);
+    assert!(!verdict.nis_consistent());
+    assert!(!verdict.passed());
+}
+
+
+#[test]
+fn understated_process_noise_fails() {
+    let truth = constant_velocity();
+    let (x0, p0) = initial();
+    let mut filter = truth.clone();
+    filter.q = truth.q.scale(0.01);
+    let verdict = evaluate(&truth, &filter, &x0, &p0, RUNS, STEPS, ALPHA, SEED);
+    assert!(!verdict.nees_consistent());
+    assert!(!verdict.passed());
+}
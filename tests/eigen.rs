@@ -0,0 +1,129 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::matrix::{Matrix, NotSymmetric};
+
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+
+fn diag(values: &[f64]) -> Matrix<f64> {
+    let mut res = Matrix::zero(values.len(), values.len());
+    for i in range(0, values.len()) {
+        res[(i, i)] = values[i];
+    }
+    res
+}
+
+
+fn symmetric() -> Matrix<f64> {
+    Matrix::new(vec![vec![4.0, 1.0, 2.0, 0.5],
+                     vec![1.0, 3.0, 0.0, 1.0],
+                     vec![2.0, 0.0, 5.0, 1.5],
+                     vec![0.5, 1.0, 1.5, 4.0]]).unwrap()
+}
+
+
+#[test]
+fn eigen_of_diagonal_is_sorted() {
+    let e = diag(&[1.0, 3.0, 2.0]).symmetric_eigen().unwrap();
+    assert_eq!(e.values(), vec![3.0, 2.0, 1.0]);
+    let v = e.vectors();
+    assert!(close(v[(1, 0)].abs(), 1.0));
+    assert!(close(v[(2, 1)].abs(), 1.0));
+    assert!(close(v[(0, 2)].abs(), 1.0));
+}
+
+
+#[test]
+fn eigen_of_known_2x2() {
+    // [2 1; 1 2] has eigenvalues 3 and 1, along (1, 1) and (1, -1)
+    let a = Matrix::new(vec![vec![2.0, 1.0], vec![1.0, 2.0]]).unwrap();
+    let e = a.symmetric_eigen().unwrap();
+    let values = e.values();
+    assert!(close(values[0], 3.0));
+    assert!(close(values[1], 1.0));
+    let v = e.vectors();
+    let r = 0.5f64.sqrt();
+    assert!(close(v[(0, 0)].abs(), r) && close(v[(0, 0)], v[(1, 0)]));
+    assert!(close(v[(0, 1)].abs(), r) && close(v[(0, 1)], -v[(1, 1)]));
+}
+
+
+#[test]
+fn eigen_reconstructs() {
+    let a = symmetric();
+    let e = a.symmetric_eigen().unwrap();
+    let v = e.vectors();
+    assert!((v * diag(e.values().as_slice()) * v.transpose()).max_abs_diff(&a) < 1e-9);
+    assert!((v.transpose() * v).max_abs_diff(&Matrix::identity(4)) < 1e-9);
+}
+
+
+#[test]
+fn eigen_rejects_nonsymmetric() {
+    let a = Matrix::new(vec![vec![1.0, 2.0], vec![0.0, 1.0]]).unwrap();
+    assert_eq!(a.symmetric_eigen().err(), Some(NotSymmetric));
+}
+
+
+#[test]
+fn svd_of_known_2x2() {
+    // [3 0; 4 5] has singular values 3 sqrt(5) and sqrt(5)
+    let a = Matrix::new(vec![vec![3.0, 0.0], vec![4.0, 5.0]]).unwrap();
+    let s = a.svd().singular_values();
+    assert!(close(s[0], 3.0 * 5.0f64.sqrt()));
+    assert!(close(s[1], 5.0f64.sqrt()));
+    assert!(close(a.condition_number(), 3.0));
+}
+
+
+#[test]
+fn svd_reconstructs_tall_and_wide() {
+    let tall = Matrix::new(vec![vec![1.0, 2.0],
+                                vec![3.0, 4.0],
+                                vec![5.0, 6.5]]).unwrap();
+    for a in [tall.clone(), tall.transpose()].iter() {
+        let svd = a.svd();
+        let (u, v) = (svd.u(), svd.v());
+        assert_eq!((u.dimx, u.dimy, v.dimx, v.dimy), (a.dimx, 2, a.dimy, 2));
+        assert!((u * diag(svd.singular_values().as_slice()) * v.transpose()).max_abs_diff(a) < 1e-9);
+        assert!((u.transpose() * u).max_abs_diff(&Matrix::identity(2)) < 1e-9);
+        assert!((v.transpose() * v).max_abs_diff(&Matrix::identity(2)) < 1e-9);
+    }
+}
+
+
+#[test]
+fn svd_matches_eigen_for_spd() {
+    let a = symmetric();
+    let s = a.svd().singular_values();
+    let values = a.symmetric_eigen().unwrap().values();
+    for i in range(0, 4) {
+        assert!(close(s[i], values[i]));
+    }
+}
+
+
+#[test]
+fn svd_of_singular_matrix() {
+    let a = Matrix::new(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).unwrap();
+    let svd = a.svd();
+    assert_eq!(svd.rank(1e-9), 1);
+    assert!(svd.condition_number() > 1e12);
+    assert!(close(diag(&[1.0, 1e-3]).condition_number(), 1000.0));
+}
+
+
+// NaN sorts last instead of failing the sort
+#[test]
+fn nan_does_not_panic() {
+    let nan: f64 = Float::nan();
+    let values = diag(&[1.0, nan, 3.0]).symmetric_eigen().unwrap().values();
+    assert!(values[0] == 3.0 && values[1] == 1.0 && values[2].is_nan());
+
+    let a = Matrix::new(vec![vec![1.0, nan], vec![2.0, 4.0]]).unwrap();
+    assert_eq!(a.svd().singular_values().len(), 2);
+}
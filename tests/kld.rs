@@ -0,0 +1,80 @@
+extern crate cs373;
+
+use std::rand::{Rng, SeedableRng, StdRng};
+use cs373::kld::Kld;
+use cs373::stats::normal_quantile;
+use cs373::particle::{ParticleFilter, MotionModel};
+use cs373::pose::Pose;
+
+
+// moves x and y each by up to the given distance, at random
+struct Scatter(f32);
+
+
+impl MotionModel<Pose, ()> for Scatter {
+    fn sample<R: Rng>(&self, state: &Pose, _: &(), rng: &mut R) -> Pose {
+        let Scatter(d) = *self;
+        Pose::new(state.x + rng.gen::<f32>() * d, state.y + rng.gen::<f32>() * d, state.orientation)
+    }
+}
+
+
+#[test]
+fn normal_quantile_known_values() {
+    assert!(normal_quantile(0.5).abs() < 1e-3);
+    assert!((normal_quantile(0.975) - 1.96).abs() < 1e-3);
+    assert!((normal_quantile(0.01) + 2.326).abs() < 1e-3);
+}
+
+
+#[test]
+fn required_grows_with_bins() {
+    let kld = Kld::new(1.0, 0.1).with_limits(10, 100000);
+    assert_eq!(kld.required(1), 10);
+    assert!(kld.required(10) < kld.required(100));
+    assert!(kld.required(100) < kld.required(1000));
+    // tighter bounds need more particles
+    let tight = kld.clone().with_bounds(0.01, 0.01);
+    assert!(tight.required(100) > kld.required(100));
+}
+
+
+#[test]
+fn required_respects_limits() {
+    let kld = Kld::new(1.0, 0.1).with_limits(50, 200);
+    assert_eq!(kld.required(2), 50);
+    assert_eq!(kld.required(100000), 200);
+}
+
+
+#[test]
+fn spread_cloud_keeps_more_particles_than_converged_one() {
+    let mut rng: StdRng = SeedableRng::from_seed([373u].as_slice());
+    let kld = Kld::new(1.0, 0.5).with_limits(20, 2000);
+    let spread = Vec::from_fn(1000, |i| {
+        Pose::new((i % 100) as f32, (i / 10) as f32, i as f32 * 0.01)
+    });
+    let converged = Vec::from_elem(1000, Pose::new(50.0, 50.0, 1.0));
+    let mut a = ParticleFilter::new(spread);
+    let mut b = ParticleFilter::new(converged);
+    let na = a.predict_kld(&Scatter(0.0), &(), &kld, &mut rng);
+    let nb = b.predict_kld(&Scatter(0.0), &(), &kld, &mut rng);
+    assert_eq!(na, a.len());
+    assert_eq!(nb, 20);
+    assert!(na > 10 * nb);
+}
+
+
+// the bins are counted after the motion, so a motion that spreads a converged cloud
+// asks for more particles
+#[test]
+fn bins_are_of_moved_particles() {
+    let mut rng: StdRng = SeedableRng::from_seed([373u].as_slice());
+    let kld = Kld::new(1.0, 0.5).with_limits(20, 2000);
+    let converged = Vec::from_elem(1000, Pose::new(50.0, 50.0, 1.0));
+    let mut still = ParticleFilter::new(converged.clone());
+    let mut moved = ParticleFilter::new(converged);
+    assert_eq!(still.predict_kld(&Scatter(0.0), &(), &kld, &mut rng), 20);
+    assert!(moved.predict_kld(&Scatter(20.0), &(), &kld, &mut rng) > 100);
+    assert!(moved.particles().iter().any(|p| p.x != 50.0));
+}
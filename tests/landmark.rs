@@ -0,0 +1,133 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::models::{Detection, RangeBearingModel};
+use cs373::particle::MeasurementModel;
+use cs373::pose::Pose;
+use cs373::seed::seeded;
+
+
+fn sensor() -> RangeBearingModel {
+    let pi: f32 = Float::pi();
+    RangeBearingModel::new(vec![(10.0, 0.0), (0.0, 10.0), (-10.0, 0.0), (50.0, 0.0)], 0.5, 0.05)
+        .with_limits(20.0, pi)
+        .with_detection(0.9, 0.5)
+}
+
+
+#[test]
+fn only_landmarks_in_view_are_visible() {
+    let s = sensor();
+    let pose = Pose::new(0.0, 0.0, 0.0);
+    let pi: f32 = Float::pi();
+    let (r, b) = s.expected(&pose, 0).unwrap();
+    assert!((r - 10.0).abs() < 1e-5 && b.abs() < 1e-5);
+    let (_, b) = s.expected(&pose, 1).unwrap();
+    assert!((b - pi / 2.0).abs() < 1e-5);
+    assert!(s.expected(&pose, 2).is_none()); // behind
+    assert!(s.expected(&pose, 3).is_none()); // too far
+}
+
+
+#[test]
+fn sensed_scans_stay_in_view() {
+    let s = sensor();
+    let pose = Pose::new(0.0, 0.0, 0.0);
+    let mut rng = seeded(5);
+    let (mut sightings, mut clutter) = (0u, 0u);
+    for _ in range(0u, 1000) {
+        for d in s.sense(&pose, &mut rng).iter() {
+            assert!(d.range < 21.0 && d.bearing.abs() <= s.fov / 2.0 + 0.2);
+            match d.landmark {
+                Some(i) => { assert!(i < 2); sightings += 1; }
+                None => clutter += 1,
+            }
+        }
+    }
+    // two visible landmarks at p_detect 0.9, and half a clutter point per scan
+    assert!(sightings > 1700 && sightings < 1900);
+    assert!(clutter > 420 && clutter < 580);
+}
+
+
+// observations may come in any order and leave landmarks out
+#[test]
+fn likelihood_ignores_order_and_handles_missing() {
+    let s = sensor();
+    let pose = Pose::new(0.0, 0.0, 0.0);
+    let a = Detection { range: 10.2, bearing: 0.01, landmark: Some(0) };
+    let b = Detection { range: 9.8, bearing: 1.56, landmark: Some(1) };
+    let c = Detection { range: 4.0, bearing: -0.7, landmark: None };
+    let forward = s.log_likelihood(&pose, &vec![a.clone(), b.clone(), c.clone()]);
+    let backward = s.log_likelihood(&pose, &vec![c.clone(), b.clone(), a.clone()]);
+    assert!((forward - backward).abs() < 1e-9);
+    // missing a sighting costs something, but is possible
+    let both = s.log_likelihood(&pose, &vec![a.clone(), b.clone()]);
+    let partial = s.log_likelihood(&pose, &vec![b.clone()]);
+    assert!(partial.is_finite() && partial < both);
+}
+
+
+#[test]
+fn likelihood_prefers_the_true_pose() {
+    let s = sensor();
+    let truth = Pose::new(0.0, 0.0, 0.0);
+    let z: Vec<Detection> = range(0u, 2).map(|i| {
+        let (r, b) = s.expected(&truth, i).unwrap();
+        Detection { range: r + 0.3, bearing: b - 0.02, landmark: Some(i) }
+    }).collect();
+    let here = s.log_likelihood(&truth, &z);
+    for &wrong in [Pose::new(3.0, 0.0, 0.0), Pose::new(0.0, 0.0, 0.5),
+                   Pose::new(0.0, -4.0, 0.0)].iter() {
+        assert!(s.log_likelihood(&wrong, &z) < here);
+    }
+}
+
+
+// a detection of a landmark the pose cannot see can only be clutter
+#[test]
+fn sighting_out_of_view_counts_as_clutter() {
+    let s = sensor();
+    let behind = Pose::new(0.0, 0.0, Float::pi());
+    let z = vec![Detection { range: 10.0, bearing: 0.0, landmark: Some(0) }];
+    let ll = s.log_likelihood(&behind, &z);
+    assert!(ll.is_finite());
+    let without_clutter = s.clone().with_detection(0.9, 0.0);
+    assert!(without_clutter.log_likelihood(&behind, &z) == Float::neg_infinity());
+}
+
+
+// with no clutter to fall back on, a sighting 40 sigma off is unlikely but possible
+#[test]
+fn tail_sighting_is_finite() {
+    let s = RangeBearingModel::new(vec![(10.0, 0.0)], 0.01, 0.001);
+    let pose = Pose::new(0.0, 0.0, 0.0);
+    let z = vec![Detection { range: 10.4, bearing: 0.0, landmark: Some(0) }];
+    let ll = s.log_likelihood(&pose, &z);
+    assert!(ll.is_finite() && ll < -700.0);
+}
+
+
+#[test]
+fn unknown_landmark_counts_as_clutter() {
+    let s = sensor();
+    let pose = Pose::new(0.0, 0.0, 0.0);
+    let unknown = vec![Detection { range: 4.0, bearing: -0.7, landmark: Some(7) }];
+    let unlabelled = vec![Detection { range: 4.0, bearing: -0.7, landmark: None }];
+    assert_eq!(s.log_likelihood(&pose, &unknown), s.log_likelihood(&pose, &unlabelled));
+    let without_clutter = s.clone().with_detection(0.9, 0.0);
+    assert!(without_clutter.log_likelihood(&pose, &unknown) == Float::neg_infinity());
+}
+
+
+// clutter is spread over the range, so there is none when the range is unbounded
+#[test]
+fn no_clutter_without_max_range() {
+    let s = RangeBearingModel::new(vec![(10.0, 0.0)], 0.5, 0.05).with_detection(1.0, 5.0);
+    assert_eq!(s.clutter_density(), 0.0);
+    let mut rng = seeded(5);
+    for _ in range(0u, 100) {
+        let z = s.sense(&Pose::new(0.0, 0.0, 0.0), &mut rng);
+        assert!(z.len() == 1 && z[0].landmark == Some(0));
+    }
+}
@@ -0,0 +1,69 @@
+extern crate cs373;
+
+use cs373::matrix::{Matrix, Singular, DimensionMismatch};
+
+
+fn m(rows: Vec<Vec<f64>>) -> Matrix<f64> {
+    Matrix::new(rows).unwrap()
+}
+
+
+#[test]
+fn solve_3x3() {
+    let a = m(vec![vec![ 2.0,  1.0, 1.0],
+                   vec![ 4.0, -6.0, 0.0],
+                   vec![-2.0,  7.0, 2.0]]);
+    let b = m(vec![vec![7.0], vec![-8.0], vec![18.0]]);
+    let x = a.solve(&b).unwrap();
+    assert!(x.max_abs_diff(&m(vec![vec![1.0], vec![2.0], vec![3.0]])) < 1e-12);
+    assert!((a * a.inverse()).max_abs_diff(&Matrix::identity(3)) < 1e-12);
+}
+
+
+// a zero leading pivot forces a row swap, which flips the sign
+#[test]
+fn determinant_sign_under_pivoting() {
+    let swap = m(vec![vec![0.0, 1.0],
+                      vec![1.0, 0.0]]);
+    assert_eq!(swap.determinant().unwrap(), -1.0);
+
+    let scaled_swap = m(vec![vec![0.0, 2.0, 0.0],
+                             vec![3.0, 0.0, 0.0],
+                             vec![0.0, 0.0, 4.0]]);
+    assert!((scaled_swap.determinant().unwrap() + 24.0).abs() < 1e-12);
+
+    // a 3-cycle is two swaps
+    let cycle = m(vec![vec![0.0, 1.0, 0.0],
+                       vec![0.0, 0.0, 1.0],
+                       vec![1.0, 0.0, 0.0]]);
+    assert!((cycle.determinant().unwrap() - 1.0).abs() < 1e-12);
+}
+
+
+#[test]
+fn singular() {
+    let a = m(vec![vec![1.0, 2.0],
+                   vec![2.0, 4.0]]);
+    assert!(a.lu().unwrap().is_singular());
+    assert_eq!(a.determinant().unwrap(), 0.0);
+    assert_eq!(a.solve(&m(vec![vec![1.0], vec![2.0]])), Err(Singular));
+    assert_eq!(a.checked_inverse(), Err(Singular));
+}
+
+
+#[test]
+#[should_fail]
+fn inverse_of_singular_fails() {
+    m(vec![vec![1.0, 2.0],
+           vec![2.0, 4.0]]).inverse();
+}
+
+
+#[test]
+fn dimension_mismatch() {
+    let a = m(vec![vec![1.0, 2.0, 3.0]]);
+    assert!(a.lu().is_err());
+    let sq = m(vec![vec![1.0, 0.0],
+                    vec![0.0, 1.0]]);
+    assert_eq!(sq.solve(&m(vec![vec![1.0]])), Err(DimensionMismatch((2, 1), (1, 1))));
+}
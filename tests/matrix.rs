@@ -0,0 +1,287 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::matrix::{Matrix, DimensionMismatch, NotPositiveDefinite, IndexOutOfRange};
+
+
+fn m(rows: Vec<Vec<f64>>) -> Matrix<f64> {
+    Matrix::new(rows).unwrap()
+}
+
+
+#[test]
+fn arithmetic() {
+    let a = m(vec![vec![1.0, 2.0, 3.0],
+                   vec![4.0, 5.0, 6.0]]);
+    let b = m(vec![vec![1.0, 0.0],
+                   vec![0.0, 1.0],
+                   vec![2.0, -1.0]]);
+    assert_eq!(a * b, m(vec![vec![7.0, -1.0],
+                             vec![16.0, -1.0]]));
+    assert_eq!(a + a, a.scale(2.0));
+    assert!((a - a).is_zero());
+    assert_eq!(-a, a.scale(-1.0));
+}
+
+
+// wider than a tile, so the tiled copy has a ragged edge in both directions
+#[test]
+fn transpose() {
+    let a = Matrix::from_vec(3, 70, Vec::from_fn(210, |k| k as f64)).unwrap();
+    let t = a.transpose();
+    assert_eq!((t.dimx, t.dimy), (70, 3));
+    for i in range(0u, 3) {
+        for j in range(0u, 70) {
+            assert_eq!(t[(j, i)], a[(i, j)]);
+        }
+    }
+    assert_eq!(t.transpose(), a);
+}
+
+
+#[test]
+#[should_fail]
+fn index_checks_the_column() {
+    let a = m(vec![vec![1.0, 2.0],
+                   vec![3.0, 4.0]]);
+    a[(0, 2)];
+}
+
+
+#[test]
+fn cholesky() {
+    let a = m(vec![vec![4.0, 2.0, 0.4],
+                   vec![2.0, 5.0, 1.0],
+                   vec![0.4, 1.0, 3.0]]);
+    let u = a.cholesky(1e-10);
+    for i in range(0u, 3) {
+        for j in range(0u, i) {
+            assert_eq!(u[(i, j)], 0.0);
+        }
+    }
+    assert!((u.transpose() * u).max_abs_diff(&a) < 1e-12);
+    assert!((a * a.spd_inverse()).max_abs_diff(&Matrix::identity(3)) < 1e-12);
+}
+
+
+
+#[test]
+fn dimension_mismatch() {
+    let a = m(vec![vec![1.0, 2.0, 3.0],
+                   vec![4.0, 5.0, 6.0]]);
+    let b = m(vec![vec![1.0, 2.0],
+                   vec![3.0, 4.0]]);
+    assert_eq!(a.checked_add(&b), Err(DimensionMismatch((2, 3), (2, 2))));
+    assert_eq!(a.checked_sub(&b), Err(DimensionMismatch((2, 3), (2, 2))));
+    assert_eq!(a.checked_mul(&b), Err(DimensionMismatch((3, 2), (2, 2))));
+    assert!(b.checked_mul(&a).is_ok());
+}
+
+
+#[test]
+#[should_fail]
+fn mismatched_mul_fails() {
+    let a = m(vec![vec![1.0, 2.0, 3.0]]);
+    a * a;
+}
+
+
+#[test]
+fn cholesky_not_positive_definite() {
+    let indefinite = m(vec![vec![1.0, 2.0],
+                            vec![2.0, 1.0]]);
+    assert_eq!(indefinite.checked_cholesky(1e-10), Err(NotPositiveDefinite(1)));
+    assert_eq!(m(vec![vec![-1.0]]).checked_cholesky(1e-10), Err(NotPositiveDefinite(0)));
+
+    // a zero pivot with something below it would divide by zero
+    let zero_pivot = m(vec![vec![0.0, 1.0],
+                            vec![1.0, 1.0]]);
+    assert_eq!(zero_pivot.checked_cholesky(1e-10), Err(NotPositiveDefinite(0)));
+}
+
+
+// positive semidefinite: the zero pivot's column is zero, so it factors
+#[test]
+fn cholesky_semidefinite() {
+    let a = m(vec![vec![1.0, 1.0, 0.0],
+                   vec![1.0, 1.0, 0.0],
+                   vec![0.0, 0.0, 2.0]]);
+    let u = a.checked_cholesky(1e-10).unwrap();
+    assert_eq!(u[(1, 1)], 0.0);
+    assert!((u.transpose() * u).max_abs_diff(&a) < 1e-12);
+    // but it has no inverse
+    assert_eq!(a.checked_spd_inverse(), Err(NotPositiveDefinite(1)));
+}
+
+
+#[test]
+#[should_fail]
+fn spd_inverse_of_semidefinite_fails() {
+    let a = m(vec![vec![1.0, 1.0],
+                   vec![1.0, 1.0]]);
+    a.spd_inverse();
+}
+
+#[test]
+fn take_non_square() {
+    let a = m(vec![vec![1.0, 2.0, 3.0, 4.0],
+                   vec![5.0, 6.0, 7.0, 8.0]]);
+    assert_eq!(a.take(&vec![1, 0], &vec![3, 0, 3]), m(vec![vec![8.0, 5.0, 8.0],
+                                                          vec![4.0, 1.0, 4.0]]));
+    assert!(a.checked_take(&vec![0], &vec![4]).is_err());
+}
+
+
+#[test]
+fn expand_non_square() {
+    let a = m(vec![vec![1.0, 2.0, 3.0]]);
+    let e = a.expand(2, 5, &vec![1], &vec![4, 0, 2]);
+    assert_eq!(e, m(vec![vec![0.0, 0.0, 0.0, 0.0, 0.0],
+                         vec![2.0, 0.0, 3.0, 0.0, 1.0]]));
+    assert!(a.checked_expand(2, 5, &vec![2], &vec![4, 0, 2]).is_err());
+}
+
+
+// an empty column list means the row list again, as in take
+#[test]
+fn expand_empty_columns() {
+    let a = m(vec![vec![1.0, 2.0],
+                   vec![3.0, 4.0]]);
+    let e = a.expand(3, 3, &vec![2, 0], &vec![]);
+    assert_eq!(e, m(vec![vec![4.0, 0.0, 3.0],
+                         vec![0.0, 0.0, 0.0],
+                         vec![2.0, 0.0, 1.0]]));
+    assert_eq!(e.take(&vec![2, 0], &vec![]), a);
+}
+
+
+#[test]
+fn views() {
+    let mut a = m(vec![vec![1.0, 2.0, 3.0, 4.0],
+                       vec![5.0, 6.0, 7.0, 8.0],
+                       vec![9.0, 10.0, 11.0, 12.0]]);
+    {
+        let v = a.view(1, 1, 2, 3);
+        assert_eq!((v.dimx, v.dimy), (2, 3));
+        assert_eq!(v[(1, 0)], 10.0);
+        assert_eq!(v.to_matrix(), m(vec![vec![6.0, 7.0, 8.0],
+                                         vec![10.0, 11.0, 12.0]]));
+    }
+    a.view_mut(0, 2, 2, 2).add_assign(&m(vec![vec![1.0, 1.0],
+                                              vec![1.0, 1.0]]));
+    assert_eq!(a.block(0, 1, 2, 3), m(vec![vec![2.0, 4.0, 5.0],
+                                           vec![6.0, 8.0, 9.0]]));
+    a.set_block(2, 0, &m(vec![vec![0.0, 0.0]]));
+    assert_eq!(a.row(2), [0.0, 0.0, 11.0, 12.0].as_slice());
+
+    assert_eq!(a.checked_view(2, 0, 2, 1).err(), Some(IndexOutOfRange(3, 3)));
+    assert_eq!(a.checked_view_mut(0, 3, 1, 2).err(), Some(IndexOutOfRange(4, 4)));
+    assert!(a.checked_view(3, 0, 0, 4).is_ok());
+}
+
+
+#[test]
+#[should_fail]
+fn view_index_checks_the_column() {
+    let a = Matrix::<f64>::identity(3);
+    a.view(0, 0, 2, 2)[(0, 2)];
+}
+
+
+#[test]
+fn add_at_non_square() {
+    let mut a = Matrix::zero(2, 4);
+    a.add_at(&vec![1, 0], &vec![3, 1, 3], &m(vec![vec![1.0, 2.0, 3.0],
+                                                   vec![4.0, 5.0, 6.0]]));
+    assert_eq!(a, m(vec![vec![0.0, 5.0, 0.0, 10.0],
+                         vec![0.0, 2.0, 0.0, 4.0]]));
+    assert_eq!(a.checked_add_at(&vec![0], &vec![0], &Matrix::identity(2)),
+               Err(DimensionMismatch((2, 2), (1, 1))));
+    assert_eq!(a.checked_add_at(&vec![2], &vec![0], &Matrix::identity(1)),
+               Err(IndexOutOfRange(2, 2)));
+}
+
+
+// dropping variables from the information form keeps the rest of the covariance
+// and mean as they were
+#[test]
+fn marginalize_matches_covariance_form() {
+    let omega = m(vec![vec![4.0, 1.0, 0.5, 0.0],
+                       vec![1.0, 3.0, 1.0, 0.2],
+                       vec![0.5, 1.0, 5.0, 1.0],
+                       vec![0.0, 0.2, 1.0, 2.0]]);
+    let xi = m(vec![vec![1.0], vec![-2.0], vec![0.5], vec![3.0]]);
+    let cov = omega.inverse();
+    let mu = omega.solve(&xi).unwrap();
+
+    let (omega_m, xi_m) = omega.marginalize(&xi, &vec![3, 1]).unwrap();
+    assert_eq!((omega_m.dimx, omega_m.dimy, xi_m.dimx), (2, 2, 2));
+    assert!(omega_m.inverse().max_abs_diff(&cov.take(&vec![0, 2], &vec![])) < 1e-12);
+    assert!(omega_m.solve(&xi_m).unwrap().max_abs_diff(&mu.take(&vec![0, 2], &vec![0])) < 1e-12);
+
+    assert_eq!(omega.marginalize(&xi, &vec![4]).err(), Some(IndexOutOfRange(4, 4)));
+}
+
+
+// a non-square omega and an xi of the wrong height are told apart
+#[test]
+fn marginalize_dimension_mismatch() {
+    let xi = m(vec![vec![1.0], vec![2.0]]);
+    let wide = m(vec![vec![1.0, 0.0, 0.0],
+                      vec![0.0, 1.0, 0.0]]);
+    assert_eq!(wide.marginalize(&xi, &vec![0]).err(), Some(DimensionMismatch((2, 2), (2, 3))));
+    let omega = Matrix::identity(3);
+    assert_eq!(omega.marginalize(&xi, &vec![0]).err(), Some(DimensionMismatch((3, 1), (2, 1))));
+}
+
+
+// constant velocity: a is nilpotent, so the series stops at i + a dt
+#[test]
+fn expm_nilpotent() {
+    let a = m(vec![vec![0.0, 1.0],
+                   vec![0.0, 0.0]]);
+    for &dt in [0.1f64, 2.0, 50.0].iter() {
+        let expected = m(vec![vec![1.0, dt],
+                              vec![0.0, 1.0]]);
+        assert!(a.scale(dt).expm().max_abs_diff(&expected) < 1e-12 * dt.max(1.0));
+    }
+}
+
+
+#[test]
+fn expm_diagonal() {
+    let a = m(vec![vec![1.0, 0.0, 0.0],
+                   vec![0.0, -2.0, 0.0],
+                   vec![0.0, 0.0, 0.0]]);
+    let dt = 1.5f64;
+    let e = a.scale(dt).expm();
+    let expected = m(vec![vec![dt.exp(), 0.0, 0.0],
+                          vec![0.0, (-2.0 * dt).exp(), 0.0],
+                          vec![0.0, 0.0, 1.0]]);
+    assert!(e.max_abs_diff(&expected) < 1e-10);
+}
+
+
+#[test]
+#[should_fail]
+fn expm_rejects_non_finite() {
+    let inf: f64 = Float::infinity();
+    m(vec![vec![inf, 0.0], vec![0.0, 1.0]]).expm();
+}
+
+
+// white noise acceleration: q = qc [[dt^3 / 3, dt^2 / 2], [dt^2 / 2, dt]]
+#[test]
+fn discretize_white_noise_acceleration() {
+    let a = m(vec![vec![0.0, 1.0],
+                   vec![0.0, 0.0]]);
+    let l = m(vec![vec![0.0],
+                   vec![1.0]]);
+    let qc = 2.5f64;
+    let dt = 0.3f64;
+    let (f, q) = a.discretize(&l, &m(vec![vec![qc]]), dt);
+    assert!(f.max_abs_diff(&m(vec![vec![1.0, dt], vec![0.0, 1.0]])) < 1e-12);
+    let expected = m(vec![vec![qc * dt.powi(3) / 3.0, qc * dt.powi(2) / 2.0],
+                          vec![qc * dt.powi(2) / 2.0, qc * dt]]);
+    assert!(q.max_abs_diff(&expected) < 1e-12);
+}
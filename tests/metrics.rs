@@ -0,0 +1,69 @@
+extern crate cs373;
+
+use cs373::metrics::{Evaluator, Run};
+use cs373::pose::Pose;
+
+
+fn run(errors: &[(f32, f32)]) -> Run {
+    let mut r = Run::new();
+    for &e in errors.iter() {
+        r.record(e);
+    }
+    r
+}
+
+
+#[test]
+fn errors_wrap_on_a_torus() {
+    let truth = Pose::new(1.0, 99.0, 0.1);
+    let estimate = Pose::new(99.0, 2.0, 6.2);
+    let (d, a) = Evaluator::new(5.0, 0.2).error(&truth, &estimate);
+    assert!((d - 137.89).abs() < 0.01);
+    assert!((a + 0.1832).abs() < 1e-3);
+    let (d, _) = Evaluator::new(5.0, 0.2).with_world_size(100.0).error(&truth, &estimate);
+    assert!((d - 13.0f32.sqrt()).abs() < 1e-4);
+}
+
+
+#[test]
+fn particle_error_is_weighted() {
+    let e = Evaluator::new(5.0, 0.2);
+    let ps = vec![Pose::new(0.0, 0.0, 0.0), Pose::new(10.0, 0.0, 1.0)];
+    let (d, a) = e.particle_error(&Pose::new(0.0, 0.0, 0.0), &ps, &vec![0.75, 0.25]);
+    assert!((d - 2.5).abs() < 1e-6 && (a - 0.25).abs() < 1e-6);
+}
+
+
+#[test]
+fn convergence_is_for_good() {
+    let e = Evaluator::new(5.0, 0.2);
+    // in tolerance at step 1, out again at 2, in for good from 3
+    let r = run(&[(30.0, 1.0), (4.0, 0.1), (6.0, 0.1), (3.0, -0.1), (2.0, 0.05)]);
+    assert_eq!(e.converged_at(&r), Some(3));
+    assert_eq!(e.converged_at(&run(&[(30.0, 0.0), (2.0, 0.5)])), None);
+    assert!((r.position_rmse() - (965.0f64 / 5.0).sqrt()).abs() < 1e-4);
+}
+
+
+#[test]
+fn monte_carlo_summary() {
+    let e = Evaluator::new(5.0, 0.2);
+    let runs = vec![run(&[(10.0, 0.3), (2.0, 0.1)]),
+                    run(&[(10.0, 0.3), (4.0, 0.1), (1.0, 0.0)]),
+                    run(&[(20.0, 0.3), (20.0, 0.3)])];
+    let s = e.summarize(&runs);
+    assert_eq!(s.runs, 3);
+    assert_eq!(s.steps, 7);
+    assert!((s.converged - 2.0 / 3.0).abs() < 1e-12);
+    assert_eq!(s.mean_convergence_step, Some(1.0));
+    assert!((s.max_position - 20.0).abs() < 1e-12);
+    assert!((s.mean_position - 67.0 / 7.0).abs() < 1e-9);
+    let json = s.to_json();
+    assert!(json.as_slice().starts_with("{\"runs\": 3, \"steps\": 7,"));
+    assert!(json.as_slice().contains("\"mean_convergence_step\": 1"));
+
+    let none = e.summarize(&vec![run(&[(20.0, 0.3)])]);
+    let json = none.to_json();
+    assert!(json.as_slice().contains("\"converged\": 0"));
+    assert!(json.as_slice().ends_with("\"mean_convergence_step\": null}"));
+}
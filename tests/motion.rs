@@ -0,0 +1,98 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::matrix::Matrix;
+use cs373::motion::{DiffDrive, Odometry, LinearizedMotion};
+use cs373::particle::MotionModel;
+use cs373::pose::{Pose, angle_diff};
+use cs373::seed::seeded;
+
+
+fn close(a: &Pose, b: &Pose, tol: f32) -> bool {
+    (a.x - b.x).abs() < tol && (a.y - b.y).abs() < tol &&
+        angle_diff(a.orientation, b.orientation).abs() < tol
+}
+
+
+// Jacobian of f by central differences, one column per input
+fn numeric_jacobian(n: uint, f: |&Vec<f32>| -> Pose, at: &Vec<f32>) -> Matrix<f64> {
+    let h = 1e-3f32;
+    let mut jac = Matrix::zero(3, n);
+    for k in range(0, n) {
+        let mut lo = at.clone();
+        let mut hi = at.clone();
+        *lo.get_mut(k) -= h;
+        *hi.get_mut(k) += h;
+        let (a, b) = (f(&lo), f(&hi));
+        jac[(0, k)] = ((b.x - a.x) / (2.0 * h)) as f64;
+        jac[(1, k)] = ((b.y - a.y) / (2.0 * h)) as f64;
+        jac[(2, k)] = (angle_diff(b.orientation, a.orientation) / (2.0 * h)) as f64;
+    }
+    jac
+}
+
+
+#[test]
+fn diff_drive_straight_and_on_the_spot() {
+    let dd = DiffDrive::new(0.5, 0.0);
+    let start = Pose::new(1.0, 2.0, 0.0);
+    assert!(close(&dd.mean(&start, &(1.0, 1.0)), &Pose::new(2.0, 2.0, 0.0), 1e-6));
+    let pi: f32 = Float::pi();
+    let (l, r) = dd.inverse(0.0, pi / 2.0);
+    assert!(close(&dd.mean(&start, &(l, r)), &Pose::new(1.0, 2.0, pi / 2.0), 1e-6));
+    let (d, w) = dd.forward(dd.inverse(0.7, 0.3));
+    assert!((d - 0.7).abs() < 1e-6 && (w - 0.3).abs() < 1e-6);
+}
+
+
+#[test]
+fn diff_drive_jacobians_match_finite_differences() {
+    let dd = DiffDrive::new(0.5, 0.1);
+    let pose = Pose::new(1.0, 2.0, 0.4);
+    let u = (0.3f32, 0.5f32);
+    let g = numeric_jacobian(3, |p| dd.mean(&Pose::new(p[0], p[1], p[2]), &u),
+                             &vec![1.0, 2.0, 0.4]);
+    assert!(g.max_abs_diff(&dd.state_jacobian(&pose, &u)) < 1e-2);
+    let v = numeric_jacobian(2, |c| dd.mean(&pose, &(c[0], c[1])), &vec![0.3, 0.5]);
+    assert!(v.max_abs_diff(&dd.control_jacobian(&pose, &u)) < 1e-2);
+}
+
+
+#[test]
+fn odometry_replays_the_odometry_step() {
+    let before = Pose::new(0.0, 0.0, 0.0);
+    let after = Pose::new(1.0, 1.0, 2.0);
+    let odo = Odometry::new(0.0, 0.0, 0.0, 0.0);
+    // from the same start the noiseless motion lands where odometry said
+    assert!(close(&odo.mean(&before, &(before.clone(), after.clone())), &after, 1e-5));
+    // and from elsewhere it moves the same way relative to the heading
+    let start = Pose::new(5.0, 5.0, Float::frac_pi_2());
+    let moved = odo.sample(&start, &(before.clone(), after.clone()), &mut seeded(1));
+    assert!(close(&moved, &Pose::new(4.0, 6.0, Float::frac_pi_2() + 2.0), 1e-5));
+}
+
+
+#[test]
+fn odometry_noise_grows_with_motion() {
+    let odo = Odometry::new(0.1, 0.01, 0.1, 0.01);
+    let (a, b, c) = odo.variances(Odometry::decompose(&Pose::new(0.0, 0.0, 0.0),
+                                                      &Pose::new(1.0, 0.0, 0.0)));
+    let (d, e, f) = odo.variances(Odometry::decompose(&Pose::new(0.0, 0.0, 0.0),
+                                                      &Pose::new(4.0, 0.0, 0.0)));
+    assert!(d > a && e > b && f > c);
+}
+
+
+#[test]
+fn ekf_prediction_grows_the_covariance() {
+    let odo = Odometry::new(0.05, 0.05, 0.05, 0.05);
+    let u = (Pose::new(0.0, 0.0, 0.0), Pose::new(2.0, 0.0, 0.3));
+    let sigma = Matrix::identity(3).scale(0.01);
+    let (mean, sigma2) = odo.propagate(&Pose::new(0.0, 0.0, 0.0), &sigma, &u);
+    assert!(close(&mean, &Pose::new(2.0, 0.0, 0.3), 1e-5));
+    for i in range(0u, 3) {
+        assert!(sigma2[(i, i)] > sigma[(i, i)]);
+    }
+    // heading uncertainty turns into sideways uncertainty
+    assert!(sigma2[(1, 1)] > 0.02);
+}
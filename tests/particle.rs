@@ -0,0 +1,161 @@
+extern crate cs373;
+
+use std::num::Float;
+use std::rand::{SeedableRng, StdRng};
+use cs373::particle::{ParticleFilter, MeasurementModel, log_sum_exp};
+use cs373::pose::Pose;
+use cs373::models::RangeModel;
+
+
+fn landmarks(n: uint) -> Vec<(f32, f32)> {
+    range(0, n).map(|i| ((i * 7 % 100) as f32, (i * 13 % 100) as f32)).collect()
+}
+
+
+// the state is its own log likelihood, to set up weights directly
+struct Given;
+
+
+impl MeasurementModel<f64, ()> for Given {
+    fn log_likelihood(&self, state: &f64, _: &()) -> f64 {
+        *state
+    }
+}
+
+
+fn weighed(log_likelihoods: Vec<f64>) -> ParticleFilter<f64> {
+    let mut pf = ParticleFilter::new(log_likelihoods);
+    assert!(pf.update(&Given, &()));
+    pf
+}
+
+
+fn rng() -> StdRng {
+    SeedableRng::from_seed([373u].as_slice())
+}
+
+
+#[test]
+fn log_sum_exp_matches_direct_sum() {
+    let xs = vec![-1.0f64, 0.5, 2.0];
+    let direct = xs.iter().fold(0.0, |a, &x| a + x.exp()).ln();
+    assert!((log_sum_exp(&xs) - direct).abs() < 1e-12);
+}
+
+
+#[test]
+fn log_sum_exp_does_not_underflow() {
+    let xs = vec![-2000.0f64, -2000.0];
+    assert!((log_sum_exp(&xs) - (-2000.0 + 2.0f64.ln())).abs() < 1e-9);
+    assert_eq!(log_sum_exp(&vec![Float::neg_infinity(), Float::neg_infinity()]),
+               Float::neg_infinity());
+}
+
+
+// With many landmarks and little noise the product of densities is 0 for every
+// particle, but the closer particle should still win.
+#[test]
+fn update_survives_underflow() {
+    let model = RangeModel::new(landmarks(200), 0.05);
+    let near = Pose::new(50.0, 50.0, 0.0);
+    let far = Pose::new(51.0, 50.0, 0.0);
+    let z = model.expected(&near);
+    let mut pf = ParticleFilter::new(vec![near, far]);
+    assert!(pf.update(&model, &z));
+    assert_eq!(pf.collapses(), 0);
+    assert!(pf.weights()[0] > 0.99);
+    let total = pf.weights().iter().fold(0.0, |a, &w| a + w);
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+
+// far out in the tail the densities are all 0, but their logs are not
+#[test]
+fn tail_measurement_is_weighed() {
+    let model = RangeModel::new(landmarks(3), 0.01);
+    let near = Pose::new(12.0, 10.0, 0.0);
+    let far = Pose::new(50.0, 50.0, 0.0);
+    let z = model.expected(&Pose::new(10.0, 10.0, 0.0));
+    let mut pf = ParticleFilter::new(vec![far, near]);
+    assert!(pf.update(&model, &z));
+    assert_eq!(pf.collapses(), 0);
+    assert!(pf.last_log_likelihood().is_finite());
+    assert!(pf.weights()[1] > 0.99);
+}
+
+
+#[test]
+fn impossible_measurement_keeps_weights() {
+    let mut pf = ParticleFilter::new(vec![Float::neg_infinity(), Float::neg_infinity()]);
+    assert!(!pf.update(&Given, &()));
+    assert_eq!(pf.collapses(), 1);
+    assert_eq!(pf.weights(), &vec![0.5, 0.5]);
+}
+
+
+// one bad particle drops out instead of discarding the measurement
+#[test]
+fn nan_likelihood_is_impossible() {
+    let mut pf = ParticleFilter::new(vec![0.0, Float::nan(), 0.0]);
+    assert!(pf.update(&Given, &()));
+    assert_eq!(pf.collapses(), 0);
+    assert_eq!(pf.weights()[1], 0.0);
+    assert!((pf.weights()[0] - 0.5).abs() < 1e-12 && (pf.weights()[2] - 0.5).abs() < 1e-12);
+}
+
+
+// an infinite likelihood can't be normalized, so it is a collapse rather than NaN weights
+#[test]
+fn infinite_likelihood_keeps_weights() {
+    let mut pf = ParticleFilter::new(vec![0.0, Float::infinity(), 0.0]);
+    assert!(!pf.update(&Given, &()));
+    assert_eq!(pf.collapses(), 1);
+    assert!(pf.weights().iter().all(|&w| (w - 1.0 / 3.0).abs() < 1e-12));
+    assert!(pf.last_log_likelihood() == Float::neg_infinity());
+}
+
+
+#[test]
+fn uniform_weights_do_not_resample() {
+    let mut pf = weighed(vec![0.0, 0.0, 0.0, 0.0]);
+    assert!((pf.effective_sample_size() - 4.0).abs() < 1e-12);
+    assert!(!pf.resample_if_needed(&mut rng()));
+}
+
+
+#[test]
+fn dominant_weight_resamples() {
+    let mut pf = weighed(vec![-50.0, 0.0, -50.0, -50.0]);
+    assert!((pf.effective_sample_size() - 1.0).abs() < 1e-6);
+    assert!(pf.resample_if_needed(&mut rng()));
+    assert_eq!(pf.particles(), &vec![0.0, 0.0, 0.0, 0.0]);
+    assert_eq!(pf.weights(), &vec![0.25, 0.25, 0.25, 0.25]);
+}
+
+
+// without a resample the next measurement multiplies into the old weights
+#[test]
+fn weights_persist_without_resampling() {
+    let mut pf = weighed(vec![0.0, -0.1, 0.0, -0.1]);
+    assert!(!pf.resample_if_needed(&mut rng()));
+    assert!(pf.update(&Given, &()));
+    let ratio = pf.weights()[1] / pf.weights()[0];
+    assert!((ratio - (-0.2f64).exp()).abs() < 1e-12);
+}
+
+
+// resampling starts once the effective sample size is below half the particles
+#[test]
+fn resample_threshold_is_half() {
+    // about two particles' worth of weight: just above and just below n / 2
+    let mut pf = weighed(vec![0.0, 0.0, -3.0, -1000.0]);
+    assert!(pf.effective_sample_size() > 2.0 && pf.effective_sample_size() < 2.2);
+    assert!(!pf.resample_if_needed(&mut rng()));
+
+    let mut pf = weighed(vec![0.0, -0.2, -1000.0, -1000.0]);
+    assert!(pf.effective_sample_size() < 2.0 && pf.effective_sample_size() > 1.9);
+    assert!(pf.resample_if_needed(&mut rng()));
+
+    let mut pf = weighed(vec![0.0, 0.0, -3.0, -1000.0]).with_resample_threshold(0.6);
+    assert!(pf.resample_if_needed(&mut rng()));
+}
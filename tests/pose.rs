@@ -0,0 +1,22 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::pose::{modulo, angle_diff};
+
+
+#[test]
+fn modulo_stays_below_m() {
+    let two_pi: f32 = Float::two_pi();
+    assert_eq!(modulo(-1e-9, two_pi), 0.0);
+    assert!((modulo(-1.0, two_pi) - (two_pi - 1.0)).abs() < 1e-6);
+    assert!((modulo(7.0, two_pi) - (7.0 - two_pi)).abs() < 1e-6);
+    assert_eq!(modulo(two_pi, two_pi), 0.0);
+}
+
+
+#[test]
+fn angle_diff_wraps() {
+    let pi: f32 = Float::pi();
+    assert!((angle_diff(0.1, 2.0 * pi - 0.1) - 0.2).abs() < 1e-6);
+    assert!((angle_diff(2.0 * pi - 0.1, 0.1) + 0.2).abs() < 1e-6);
+}
@@ -0,0 +1,47 @@
+extern crate cs373;
+
+use cs373::matrix::{Matrix, Singular};
+
+
+fn m(rows: Vec<Vec<f64>>) -> Matrix<f64> {
+    Matrix::new(rows).unwrap()
+}
+
+
+#[test]
+fn q_is_orthogonal() {
+    let a = m(vec![vec![1.0, 2.0],
+                   vec![3.0, 4.0],
+                   vec![5.0, 7.0]]);
+    let q = a.qr().q();
+    assert!((q.transpose() * q).max_abs_diff(&Matrix::identity(3)) < 1e-12);
+}
+
+
+// the third row is the sum of the first two
+#[test]
+fn rank_deficient() {
+    let a = m(vec![vec![1.0, 2.0, 3.0],
+                   vec![0.0, 1.0, 4.0],
+                   vec![1.0, 3.0, 7.0]]);
+    assert_eq!(a.rank(), 2);
+    assert_eq!(a.least_squares(&m(vec![vec![1.0], vec![1.0], vec![1.0]])), Err(Singular));
+    assert_eq!(Matrix::<f64>::zero(3, 2).rank(), 0);
+    assert_eq!(a.transpose().rank(), 2);
+}
+
+
+// line fit through five points, against the normal equations
+#[test]
+fn overdetermined_least_squares() {
+    let a = m(vec![vec![1.0, 0.0],
+                   vec![1.0, 1.0],
+                   vec![1.0, 2.0],
+                   vec![1.0, 3.0],
+                   vec![1.0, 4.0]]);
+    let b = m(vec![vec![1.1], vec![2.9], vec![5.2], vec![6.8], vec![9.1]]);
+    assert_eq!(a.rank(), 2);
+    let x = a.least_squares(&b).unwrap();
+    let normal = (a.transpose() * a).solve(&(a.transpose() * b)).unwrap();
+    assert!(x.max_abs_diff(&normal) < 1e-10);
+}
@@ -0,0 +1,74 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::grid::OccupancyGrid;
+use cs373::rangefinder::{BeamModel, LikelihoodFieldModel, scan_angles};
+use cs373::particle::{ParticleFilter, MeasurementModel};
+use cs373::pose::Pose;
+
+
+// a 10 x 10 room of half-metre cells, walled in, with a pillar off centre
+fn room() -> OccupancyGrid {
+    let mut g = OccupancyGrid::new(20, 20, 0.5);
+    for k in range(0u, 20) {
+        g.set(k, 0, true);
+        g.set(k, 19, true);
+        g.set(0, k, true);
+        g.set(19, k, true);
+    }
+    g.set(13, 6, true);
+    g
+}
+
+
+#[test]
+fn ray_cast_stops_at_walls() {
+    let g = room();
+    let pi: f32 = Float::pi();
+    assert!((g.ray_cast(5.0, 5.0, 0.0, 20.0) - 4.5).abs() < 1e-4);
+    assert!((g.ray_cast(5.0, 5.0, pi, 20.0) - 4.5).abs() < 1e-4);
+    assert!((g.ray_cast(5.0, 5.0, pi / 2.0, 20.0) - 4.5).abs() < 1e-4);
+    assert!((g.ray_cast(5.0, 3.2, 0.0, 20.0) - 1.5).abs() < 1e-4);
+    assert_eq!(g.ray_cast(5.0, 5.0, 0.0, 2.0), 2.0);
+    assert_eq!(g.ray_cast(0.2, 0.2, 0.0, 2.0), 0.0);
+}
+
+
+#[test]
+fn distance_field_measures_to_nearest_obstacle() {
+    let g = room();
+    let d = g.distance_field();
+    assert_eq!(d[0], 0.0);
+    assert!((d[2 * 20 + 10] - 1.0).abs() < 1e-6);
+    assert!((d[12 * 20 + 5] - 0.5f32 * 2.0f32.sqrt()).abs() < 1e-6);
+}
+
+
+#[test]
+fn beam_density_integrates_to_one() {
+    let model = BeamModel::new(room(), vec![0.0], 8.0, 0.2);
+    let steps = 8000u;
+    let dz = 8.0 / steps as f32;
+    let mut total = model.beam_prob(8.0, 3.0);
+    for k in range(0, steps) {
+        total += model.beam_prob((k as f32 + 0.5) * dz, 3.0) * dz as f64;
+    }
+    assert!((total - 1.0).abs() < 1e-3);
+}
+
+
+#[test]
+fn both_models_prefer_the_true_pose() {
+    let angles = scan_angles(16, 6.0);
+    let truth = Pose::new(3.0, 6.0, 0.3);
+    let wrong = Pose::new(6.0, 3.0, 0.3);
+    let beam = BeamModel::new(room(), angles.clone(), 8.0, 0.1);
+    let field = LikelihoodFieldModel::new(room(), angles, 8.0, 0.1);
+    let z = beam.expected(&truth);
+    assert!(beam.log_likelihood(&truth, &z) > beam.log_likelihood(&wrong, &z));
+    assert!(field.log_likelihood(&truth, &z) > field.log_likelihood(&wrong, &z));
+
+    let mut filter = ParticleFilter::new(vec![wrong.clone(), truth.clone()]);
+    filter.update(&field, &z);
+    assert!(filter.weights()[1] > 0.99);
+}
@@ -0,0 +1,100 @@
+extern crate cs373;
+
+use std::rand::Rng;
+use std::rand::distributions::{Normal, IndependentSample};
+use cs373::regularize::{Regularized, spread};
+use cs373::particle::{ParticleFilter, MotionModel, Regularize, Auxiliary};
+use cs373::pose::Pose;
+use cs373::models::{BearingModel, GpsModel};
+use cs373::seed::seeded;
+
+
+// drives d forward with a little noise
+struct Forward;
+
+impl MotionModel<Pose, f32> for Forward {
+    fn sample<R: Rng>(&self, p: &Pose, &d: &f32, rng: &mut R) -> Pose {
+        let d = d + Normal::new(0.0, 0.5).ind_sample(rng) as f32;
+        Pose::new(p.x + d * p.orientation.cos(), p.y + d * p.orientation.sin(), p.orientation)
+    }
+}
+
+
+fn distinct(ps: &Vec<Pose>) -> uint {
+    let mut n = 0;
+    for i in range(0, ps.len()) {
+        if range(0, i).all(|j| ps[j] != ps[i]) {
+            n += 1;
+        }
+    }
+    n
+}
+
+
+#[test]
+fn silverman_bandwidth() {
+    let r = Regularized::new();
+    assert!((r.bandwidth(100) - 0.5017).abs() < 1e-3);
+    assert!(r.bandwidth(1000) < r.bandwidth(100));
+    assert!((r.clone().with_scale(0.5).bandwidth(100) - 0.5 * r.bandwidth(100)).abs() < 1e-12);
+}
+
+
+#[test]
+fn spread_wraps_orientation() {
+    let ps = vec![Pose::new(0.0, 0.0, 0.1), Pose::new(2.0, 0.0, -0.1)];
+    let (sx, sy, sa) = spread(&ps, &vec![0.5, 0.5]);
+    assert!((sx - 1.0).abs() < 1e-6 && sy.abs() < 1e-6);
+    assert!((sa - 0.1).abs() < 1e-5);
+}
+
+
+// a sharp measurement leaves a handful of survivors; jitter spreads their copies out
+#[test]
+fn regularized_resampling_keeps_particles_distinct() {
+    let mut rng = seeded(9);
+    let particles = Vec::from_fn(300, |_| {
+        Pose::new(rng.gen::<f32>() * 100.0, rng.gen::<f32>() * 100.0, 0.0)
+    });
+    let sensor = GpsModel::new(2.0);
+    let mut plain = ParticleFilter::new(particles.clone());
+    plain.update(&sensor, &(50.0, 50.0));
+    plain.resample(&mut rng);
+    assert!(distinct(plain.particles()) < 50);
+
+    let mut jittered = ParticleFilter::new(particles);
+    jittered.update(&sensor, &(50.0, 50.0));
+    jittered.resample_regularized(&Regularized::new(), &mut rng);
+    assert_eq!(distinct(jittered.particles()), 300);
+    let (sx, sy, _) = spread(jittered.particles(), jittered.weights());
+    assert!(sx < 5.0 && sy < 5.0);
+}
+
+
+fn final_error(truth: &Pose, filter: &ParticleFilter<Pose>) -> f32 {
+    let best = &filter.hypotheses(5.0, 0.5)[0];
+    truth.distance_to((best.pose.x, best.pose.y))
+}
+
+
+#[test]
+fn auxiliary_and_regularized_track() {
+    let landmarks = vec![(0.0, 100.0), (0.0, 0.0), (100.0, 0.0), (100.0, 100.0)];
+    let sensor = BearingModel::new(landmarks, 0.1);
+    let mut rng = seeded(4);
+    let start = Vec::from_fn(1000, |_| {
+        Pose::new(rng.gen::<f32>() * 100.0, rng.gen::<f32>() * 100.0, 0.0)
+    });
+    let mut auxiliary = ParticleFilter::new(start.clone()).with_engine(Auxiliary);
+    let mut regularized = ParticleFilter::new(start).with_engine(Regularize(Regularized::new()));
+    let mut truth = Pose::new(20.0, 30.0, 0.0);
+    for _ in range(0u, 10) {
+        truth = Forward.sample(&truth, &5.0, &mut rng);
+        let z = sensor.expected(&truth);
+        auxiliary.step(&Forward, &5.0, &sensor, &z, &mut rng);
+        assert_eq!(auxiliary.collapses(), 0);
+        regularized.step(&Forward, &5.0, &sensor, &z, &mut rng);
+    }
+    assert!(final_error(&truth, &auxiliary) < 5.0);
+    assert!(final_error(&truth, &regularized) < 5.0);
+}
@@ -0,0 +1,142 @@
+extern crate cs373;
+
+use std::num::Float;
+use std::rand::{SeedableRng, StdRng};
+use cs373::resample::{Resampler, Wheel, Multinomial, Stratified, Systematic, Residual};
+
+
+static N: uint = 10;
+static TRIALS: uint = 20000;
+static WEIGHTS: [f64, ..4] = [0.07, 0.18, 0.33, 0.42];
+
+
+// mean and variance over the trials of how often each particle is copied
+fn count_stats(resampler: Resampler) -> (Vec<f64>, Vec<f64>) {
+    let mut rng: StdRng = SeedableRng::from_seed([373u].as_slice());
+    let weights = WEIGHTS.to_vec();
+    let k = weights.len();
+    let mut sum = Vec::from_elem(k, 0.0f64);
+    let mut sum2 = Vec::from_elem(k, 0.0f64);
+    for _ in range(0, TRIALS) {
+        let mut counts = Vec::from_elem(k, 0.0f64);
+        for &i in resampler.indices(&weights, N, &mut rng).iter() {
+            *counts.get_mut(i) += 1.0;
+        }
+        for i in range(0, k) {
+            *sum.get_mut(i) += counts[i];
+            *sum2.get_mut(i) += counts[i] * counts[i];
+        }
+    }
+    let t = TRIALS as f64;
+    let mean: Vec<f64> = sum.iter().map(|&s| s / t).collect();
+    let var = range(0, k).map(|i| sum2[i] / t - mean[i] * mean[i]).collect();
+    (mean, var)
+}
+
+
+// variance of the count under n independent draws
+fn multinomial_var(w: f64) -> f64 {
+    N as f64 * w * (1.0 - w)
+}
+
+
+#[test]
+fn draws_n_indices() {
+    let mut rng: StdRng = SeedableRng::from_seed([1u].as_slice());
+    for &r in [Wheel, Multinomial, Stratified, Systematic, Residual].iter() {
+        let indices = r.indices(&WEIGHTS.to_vec(), 7, &mut rng);
+        assert_eq!(indices.len(), 7);
+        assert!(indices.iter().all(|&i| i < WEIGHTS.len()));
+    }
+}
+
+
+#[test]
+fn unbiased() {
+    // the wheel starts from a uniformly chosen particle, which biases it slightly
+    for &(r, tol) in [(Wheel, 0.1), (Multinomial, 0.05), (Stratified, 0.05),
+                      (Systematic, 0.05), (Residual, 0.05)].iter() {
+        let (mean, _) = count_stats(r);
+        for i in range(0, WEIGHTS.len()) {
+            let expected = N as f64 * WEIGHTS[i];
+            assert!((mean[i] - expected).abs() < tol,
+                    "{}: particle {} copied {} times on average, expected {}", r, i, mean[i], expected);
+        }
+    }
+}
+
+
+#[test]
+fn multinomial_variance() {
+    let (_, var) = count_stats(Multinomial);
+    for i in range(0, WEIGHTS.len()) {
+        let expected = multinomial_var(WEIGHTS[i]);
+        assert!((var[i] - expected).abs() < 0.1 * expected);
+    }
+}
+
+
+#[test]
+fn lower_variance_than_multinomial() {
+    for &r in [Stratified, Systematic, Residual].iter() {
+        let (_, var) = count_stats(r);
+        for i in range(0, WEIGHTS.len()) {
+            assert!(var[i] < multinomial_var(WEIGHTS[i]),
+                    "{}: variance {} for particle {}", r, var[i], i);
+        }
+    }
+}
+
+
+#[test]
+fn systematic_and_residual_counts() {
+    let mut rng: StdRng = SeedableRng::from_seed([2u].as_slice());
+    let weights = WEIGHTS.to_vec();
+    for _ in range(0, 1000u) {
+        for &r in [Systematic, Residual].iter() {
+            let mut counts = Vec::from_elem(weights.len(), 0u);
+            for &i in r.indices(&weights, N, &mut rng).iter() {
+                *counts.get_mut(i) += 1;
+            }
+            for i in range(0, weights.len()) {
+                let expected = N as f64 * weights[i];
+                // never fewer than floor(n w) copies, and systematic never more than ceil
+                assert!(counts[i] as f64 >= expected.floor());
+                if r == Systematic {
+                    assert!(counts[i] as f64 <= expected.ceil());
+                }
+            }
+        }
+    }
+}
+
+
+#[test]
+fn unnormalized_weights() {
+    let mut rng: StdRng = SeedableRng::from_seed([3u].as_slice());
+    let weights = vec![0.0, 2.0, 0.0, 6.0];
+    for &r in [Wheel, Multinomial, Stratified, Systematic, Residual].iter() {
+        let indices = r.indices(&weights, 100, &mut rng);
+        assert!(indices.iter().all(|&i| i == 1 || i == 3));
+    }
+}
+
+
+// weights that say nothing about the particles keep them all alike
+#[test]
+fn zero_weights_are_uniform() {
+    let mut rng: StdRng = SeedableRng::from_seed([4u].as_slice());
+    for weights in [vec![0.0, 0.0, 0.0, 0.0], vec![0.5, Float::nan(), 0.2, 0.3]].iter() {
+        for &r in [Wheel, Multinomial, Stratified, Systematic, Residual].iter() {
+            let indices = r.indices(weights, 8, &mut rng);
+            assert_eq!(indices.len(), 8);
+            if r == Systematic || r == Residual {
+                for i in range(0, 4u) {
+                    assert_eq!(indices.iter().filter(|&&j| j == i).count(), 2);
+                }
+            } else {
+                assert!(indices.iter().all(|&i| i < 4));
+            }
+        }
+    }
+}
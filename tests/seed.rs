@@ -0,0 +1,35 @@
+extern crate cs373;
+
+use std::rand::Rng;
+use cs373::seed::seeded;
+use cs373::particle::ParticleFilter;
+use cs373::pose::Pose;
+use cs373::models::RangeModel;
+
+
+#[test]
+fn same_seed_same_numbers() {
+    let a: Vec<f64> = seeded(42).gen_iter().take(10).collect();
+    let b: Vec<f64> = seeded(42).gen_iter().take(10).collect();
+    let c: Vec<f64> = seeded(43).gen_iter().take(10).collect();
+    assert_eq!(a, b);
+    assert!(a != c);
+}
+
+
+// a whole filter run replays exactly from its seed
+#[test]
+fn filter_replays_from_seed() {
+    let run = |seed: uint| {
+        let mut rng = seeded(seed);
+        let sensor = RangeModel::new(vec![(20.0, 20.0), (80.0, 80.0)], 5.0);
+        let particles = Vec::from_fn(200, |_| {
+            Pose::new(rng.gen::<f32>() * 100.0, rng.gen::<f32>() * 100.0, 0.0)
+        });
+        let mut filter = ParticleFilter::new(particles);
+        filter.update(&sensor, &vec![30.0, 60.0]);
+        filter.resample(&mut rng);
+        filter.particles().clone()
+    };
+    assert_eq!(run(7), run(7));
+}
@@ -0,0 +1,91 @@
+extern crate cs373;
+
+use std::num::Float;
+use cs373::matrix::{Matrix, NotPositiveDefinite};
+use cs373::sparse::Triplets;
+
+
+fn triplets(n: uint, entries: &[(uint, uint, f64)]) -> Triplets<f64> {
+    let mut t = Triplets::new(n, n);
+    for &(i, j, v) in entries.iter() {
+        t.add(i, j, v);
+    }
+    t
+}
+
+
+#[test]
+fn duplicates_are_summed() {
+    let a = triplets(2, &[(0, 0, 1.0), (1, 0, 3.0), (0, 0, 2.0), (0, 1, -1.0), (0, 1, 1.0)]).to_csr();
+    assert_eq!(a.nnz(), 3);
+    assert_eq!(a.get(0, 0), 3.0);
+    assert_eq!(a.get(0, 1), 0.0);
+    assert_eq!(a.get(1, 0), 3.0);
+    assert_eq!(a.get(1, 1), 0.0);
+}
+
+
+#[test]
+fn solve_matches_dense() {
+    let a = triplets(4, &[(0, 0, 4.0), (0, 1, 1.0), (1, 0, 1.0),
+                          (1, 1, 3.0), (1, 3, -1.0), (3, 1, -1.0),
+                          (2, 2, 2.0), (2, 3, 0.5), (3, 2, 0.5),
+                          (3, 3, 5.0)]).to_csr();
+    let b = Matrix::new(vec![vec![1.0, 0.0],
+                             vec![2.0, 1.0],
+                             vec![3.0, 0.0],
+                             vec![4.0, -1.0]]).unwrap();
+    let x = a.solve(&b).unwrap();
+    let expected = a.to_dense().solve(&b).unwrap();
+    assert!(x.max_abs_diff(&expected) < 1e-12);
+}
+
+
+#[test]
+fn indefinite_is_rejected() {
+    let a = triplets(2, &[(0, 0, 1.0), (0, 1, 2.0), (1, 0, 2.0), (1, 1, 1.0)]).to_csr();
+    match a.cholesky() {
+        Err(NotPositiveDefinite(_)) => (),
+        res => fail!("expected NotPositiveDefinite, got {}", res),
+    }
+    let nan: f64 = Float::nan();
+    match triplets(1, &[(0, 0, nan)]).to_csr().cholesky() {
+        Err(NotPositiveDefinite(0)) => (),
+        res => fail!("expected NotPositiveDefinite, got {}", res),
+    }
+}
+
+
+// x[b] - x[a] = d with unit information
+fn link(t: &mut Triplets<f64>, xi: &mut Matrix<f64>, a: uint, b: uint, d: f64) {
+    t.add(a, a, 1.0);
+    t.add(b, b, 1.0);
+    t.add(a, b, -1.0);
+    t.add(b, a, -1.0);
+    xi[(a, 0)] -= d;
+    xi[(b, 0)] += d;
+}
+
+
+// 1-D pose chain with a unit step between neighbours, anchored at 0 and closed into a
+// loop. Minimum degree keeps the factor about as sparse as the chain itself.
+#[test]
+fn loop_closure_fill_is_linear() {
+    let n = 10000u;
+    let mut t = Triplets::new(n, n);
+    let mut xi = Matrix::zero(n, 1);
+    t.add(0, 0, 1.0f64);
+    for i in range(0, n - 1) {
+        link(&mut t, &mut xi, i, i + 1, 1.0);
+    }
+    link(&mut t, &mut xi, 0, n - 1, (n - 1) as f64);
+
+    let omega = t.to_csr();
+    let chol = omega.cholesky().unwrap();
+    assert!(chol.nnz() <= 3 * n);
+
+    let mu = chol.solve(&xi).unwrap();
+    for i in range(0, n) {
+        assert!((mu[(i, 0)] - i as f64).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,83 @@
+extern crate cs373;
+
+use cs373::matrix::{Matrix, ParseError, DimensionMismatch};
+
+
+fn sample() -> Matrix<f64> {
+    Matrix::new(vec![vec![1.0, -2.5, 0.125],
+                     vec![30.0, 4.0, -0.0625]]).unwrap()
+}
+
+
+#[test]
+fn text_layout() {
+    let a = Matrix::new(vec![vec![1.0, -2.0], vec![3.0, 40.0]]).unwrap();
+    assert_eq!(a.to_text(1).as_slice(), "[[ 1.0 -2.0]\n [ 3.0 40.0]]");
+}
+
+
+#[test]
+fn text_round_trip() {
+    let a = sample();
+    let b: Matrix<f64> = Matrix::from_text(a.to_text(4).as_slice()).unwrap();
+    assert_eq!(a, b);
+}
+
+
+#[test]
+fn text_reads_numpy_repr_and_savetxt() {
+    let expected = Matrix::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap();
+    let repr: Matrix<f64> = Matrix::from_text("[[1., 2.],\n [3., 4.]]").unwrap();
+    let savetxt: Matrix<f64> = Matrix::from_text("1.0 2.0\n3.0 4.0\n").unwrap();
+    assert_eq!(repr, expected);
+    assert_eq!(savetxt, expected);
+
+    let row: Matrix<f64> = Matrix::from_text("[1 2 3]").unwrap();
+    assert_eq!((row.dimx, row.dimy), (1, 3));
+    let empty: Matrix<f64> = Matrix::from_text("[]").unwrap();
+    assert_eq!((empty.dimx, empty.dimy), (0, 0));
+}
+
+
+#[test]
+fn text_errors() {
+    let bad: Result<Matrix<f64>, _> = Matrix::from_text("[[1 2]\n [3 x]]");
+    assert_eq!(bad.err(), Some(ParseError(2, "`x` is not a number".to_string())));
+    let unmatched: Result<Matrix<f64>, _> = Matrix::from_text("[[1 2]");
+    assert!(unmatched.is_err());
+    let ragged: Result<Matrix<f64>, _> = Matrix::from_text("[[1 2] [3]]");
+    assert_eq!(ragged.err(), Some(DimensionMismatch((1, 2), (1, 1))));
+    let mixed: Result<Matrix<f64>, _> = Matrix::from_text("[[1 2] 3]");
+    assert_eq!(mixed.err(), Some(ParseError(1, "`3` next to a row".to_string())));
+    let mixed: Result<Matrix<f64>, _> = Matrix::from_text("[3\n [1 2]]");
+    assert_eq!(mixed.err(), Some(ParseError(2, "row next to a number".to_string())));
+}
+
+
+#[test]
+fn csv_round_trip() {
+    let a = sample();
+    let csv = a.to_csv();
+    assert_eq!(csv.as_slice(), "1,-2.5,0.125\n30,4,-0.0625\n");
+    let b: Matrix<f64> = Matrix::from_csv(csv.as_slice()).unwrap();
+    assert_eq!(a, b);
+}
+
+
+#[test]
+fn csv_skips_comments_and_blank_lines() {
+    let a: Matrix<f64> = Matrix::from_csv("# omega\n1, 2\n\n3, 4\n").unwrap();
+    assert_eq!(a, Matrix::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap());
+    let bad: Result<Matrix<f64>, _> = Matrix::from_csv("1,2\n3,four\n");
+    assert_eq!(bad.err(), Some(ParseError(2, "`four` is not a number".to_string())));
+}
+
+
+#[test]
+fn csv_empty_cell() {
+    let bad: Result<Matrix<f64>, _> = Matrix::from_csv("1,2,3\n1,,3\n");
+    assert_eq!(bad.err(), Some(ParseError(2, "empty cell".to_string())));
+    // runs of whitespace are not empty cells in the plain text format
+    let a: Matrix<f64> = Matrix::from_text("1   2\n3 ,\t4\n").unwrap();
+    assert_eq!(a, Matrix::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap());
+}
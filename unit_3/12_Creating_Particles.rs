@@ -1,7 +1,10 @@
-use std::rand::{random, task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::seed;
 
 #[deriving(Show,Clone)]
 struct Robot {
@@ -25,19 +28,19 @@ fn gaussian<F: Float>(mean: F, covar2: F, x: F) -> F {
     ((x - mean).powi(2) / -(covar2 + covar2)).exp() / (two_pi * covar2).sqrt()
 }
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
 
 impl Robot {
-    fn new(world_size: f32) -> Robot {
+    fn new<R: Rng>(world_size: f32, rng: &mut R) -> Robot {
         Robot {
             world_size: world_size,
-            x: random::<f32>() * world_size,
-            y: random::<f32>() * world_size,
-            orientation: random::<f32>() * Float::two_pi(),
+            x: rng.gen::<f32>() * world_size,
+            y: rng.gen::<f32>() * world_size,
+            orientation: rng.gen::<f32>() * Float::two_pi(),
             forward_noise: 0.0,
             turn_noise: 0.0,
             sense_noise: 0.0,
@@ -60,32 +63,32 @@ impl Robot {
     }
 
 #[allow(dead_code)]
-    fn sense(&self, landmarks: &Vec<(f32,f32)>) -> Vec<f32> {
+    fn sense<R: Rng>(&self, landmarks: &Vec<(f32,f32)>, rng: &mut R) -> Vec<f32> {
         let mut z: Vec<f32> = Vec::with_capacity(landmarks.len());
         for &(x,y) in landmarks.iter() {
             // sensed dist
             let mut dist = ((self.x - x).powi(2) + (self.y - y).powi(2)).sqrt();
             // add error
-            dist += gauss(0.0, self.sense_noise);
+            dist += gauss(0.0, self.sense_noise, rng);
             z.push(dist);
         }
         z
     }
 
 #[allow(dead_code)]
-    fn travel(&self, turn: f32, forward: f32) -> Robot {
+    fn travel<R: Rng>(&self, turn: f32, forward: f32, rng: &mut R) -> Robot {
         assert!(forward >= 0.0);
 
-        let mut orientation = self.orientation + turn + gauss(0.0, self.sense_noise);
+        let mut orientation = self.orientation + turn + gauss(0.0, self.sense_noise, rng);
         orientation = modulo(orientation, Float::two_pi());
 
-        let dist = forward + gauss(0.0, self.forward_noise);
+        let dist = forward + gauss(0.0, self.forward_noise, rng);
         let mut x = self.x + orientation.cos() * dist;
         let mut y = self.y + orientation.sin() * dist;
         x = modulo(x, self.world_size);
         y = modulo(y, self.world_size);
 
-        let mut res = Robot::new(self.world_size);
+        let mut res = Robot::new(self.world_size, rng);
         res.set(x, y, orientation);
         res.set_noise(self.forward_noise, self.turn_noise, self.sense_noise);
         res
@@ -118,6 +121,7 @@ fn eval(r: &Robot, ps: &Vec<Robot>) -> f32 {
 }
 
 fn main() {
+    let mut rng = seed::from_env();
     /*
     let landmarks: Vec<(f32,f32)> = vec![(20.0, 20.0),
                                          (80.0, 80.0),
@@ -128,6 +132,6 @@ fn main() {
     let world_size: f32 = 100.0;
     let n: uint = 1000;
 
-    let ps = Vec::from_fn(n, |_| Robot::new(world_size));
+    let ps = Vec::from_fn(n, |_| Robot::new(world_size, &mut rng));
     println!("{}", ps.len());
 }
@@ -1,8 +1,13 @@
-use std::iter::AdditiveIterator;
-use std::rand::{random, task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::pose::{Pose, HasPose};
+use cs373::particle::{MeasurementModel, log_sum_exp};
+use cs373::models::RangeModel;
+use cs373::seed;
 
 #[deriving(Show,Clone)]
 struct Robot {
@@ -21,24 +26,19 @@ fn modulo(n: f32, m: f32) -> f32 {
     k
 }
 
-fn gaussian<F: Float>(mean: F, covar2: F, x: F) -> F {
-    let two_pi: F = Float::two_pi();
-    ((x - mean).powi(2) / -(covar2 + covar2)).exp() / (two_pi * covar2).sqrt()
-}
-
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
 
 impl Robot {
-    fn new(world_size: f32) -> Robot {
+    fn new<R: Rng>(world_size: f32, rng: &mut R) -> Robot {
         Robot {
             world_size: world_size,
-            x: random::<f32>() * world_size,
-            y: random::<f32>() * world_size,
-            orientation: random::<f32>() * Float::two_pi(),
+            x: rng.gen::<f32>() * world_size,
+            y: rng.gen::<f32>() * world_size,
+            orientation: rng.gen::<f32>() * Float::two_pi(),
             forward_noise: 0.0,
             turn_noise: 0.0,
             sense_noise: 0.0,
@@ -60,45 +60,45 @@ impl Robot {
         self.sense_noise = new_s_noise;
     }
 
-    fn sense(&self, landmarks: &Vec<(f32,f32)>) -> Vec<f32> {
+    fn sense<R: Rng>(&self, landmarks: &Vec<(f32,f32)>, rng: &mut R) -> Vec<f32> {
         let mut z: Vec<f32> = Vec::with_capacity(landmarks.len());
         for &(x,y) in landmarks.iter() {
             // sensed dist
             let mut dist = ((self.x - x).powi(2) + (self.y - y).powi(2)).sqrt();
             // add error
-            dist += gauss(0.0, self.sense_noise);
+            dist += gauss(0.0, self.sense_noise, rng);
             z.push(dist);
         }
         z
     }
 
-    fn travel(&self, turn: f32, forward: f32) -> Robot {
+    fn travel<R: Rng>(&self, turn: f32, forward: f32, rng: &mut R) -> Robot {
         assert!(forward >= 0.0);
 
-        let mut orientation = self.orientation + turn + gauss(0.0, self.sense_noise);
+        let mut orientation = self.orientation + turn + gauss(0.0, self.sense_noise, rng);
         orientation = modulo(orientation, Float::two_pi());
 
-        let dist = forward + gauss(0.0, self.forward_noise);
+        let dist = forward + gauss(0.0, self.forward_noise, rng);
         let mut x = self.x + orientation.cos() * dist;
         let mut y = self.y + orientation.sin() * dist;
         x = modulo(x, self.world_size);
         y = modulo(y, self.world_size);
 
-        let mut res = Robot::new(self.world_size);
+        let mut res = Robot::new(self.world_size, rng);
         res.set(x, y, orientation);
         res.set_noise(self.forward_noise, self.turn_noise, self.sense_noise);
         res
     }
 
-    fn measurement_prob(&self, landmarks: &Vec<(f32,f32)>, measurement: &Vec<f32>) -> f32 {
-        assert!(landmarks.len() == measurement.len());
-        let mut prob: f32 = 1.0;
-        for i in range(0,landmarks.len()) {
-            let (x,y) = landmarks[i];
-            let dist = ((self.x - x).powi(2) + (self.y - y).powi(2)).sqrt();
-            prob = prob * gaussian(dist, self.sense_noise, measurement[i]);
-        }
-        prob
+    // log of the measurement's likelihood; a product of densities underflows to 0
+    fn measurement_log_prob(&self, landmarks: &Vec<(f32,f32)>, measurement: &Vec<f32>) -> f64 {
+        RangeModel::new(landmarks.clone(), self.sense_noise).log_likelihood(self, measurement)
+    }
+}
+
+impl HasPose for Robot {
+    fn pose(&self) -> Pose {
+        Pose::new(self.x, self.y, self.orientation)
     }
 }
 
@@ -116,51 +116,49 @@ fn eval(r: &Robot, ps: &Vec<Robot>) -> f32 {
 }
 
 fn main() {
+    let mut rng = seed::from_env();
     let landmarks: Vec<(f32,f32)> = vec![(20.0, 20.0),
                                          (80.0, 80.0),
                                          (20.0, 80.0),
                                          (80.0, 20.0)];
 
     let world_size: f32 = 100.0;
-    let mut myrobot = Robot::new(world_size);
+    let mut myrobot = Robot::new(world_size, &mut rng);
     
     let n: uint = 1000;
     let mut ps = Vec::from_fn(n, |_| {
-        let mut x = Robot::new(world_size);
+        let mut x = Robot::new(world_size, &mut rng);
         x.set_noise(0.05, 0.05, 5.0);
         x
     });
 
     for _ in range(0u,2u) {
-        myrobot = myrobot.travel(0.1, 5.0);
-        let z = myrobot.sense(&landmarks);
+        myrobot = myrobot.travel(0.1, 5.0, &mut rng);
+        let z = myrobot.sense(&landmarks, &mut rng);
 
         // travel
         for i in range(0, n) {
-            *ps.get_mut(i) = ps[i].travel(0.1, 5.0);
+            *ps.get_mut(i) = ps[i].travel(0.1, 5.0, &mut rng);
         }
 
         // weigh
-        let mut ws: Vec<f32> = Vec::with_capacity(n);
+        let mut ws: Vec<f64> = Vec::with_capacity(n);
         for i in range(0, ps.len()) {
-            ws.push(ps[i].measurement_prob(&landmarks, &z));
+            ws.push(ps[i].measurement_log_prob(&landmarks, &z));
         }
 
-        // normalize
-        let s: f32 = ws.iter().map(|&x| x).sum();
-
-        for i in range(0, n) {
-            *ws.get_mut(i) = ws[i] / s;
-        }
+        // normalize, in the log domain
+        let total = log_sum_exp(&ws);
+        let ws: Vec<f64> = ws.iter().map(|&lw| (lw - total).exp()).collect();
 
         // resample
         let mut p3: Vec<Robot> = Vec::with_capacity(n);
         let mut index = 0;
-        let mut beta = random::<f32>() * 2.0;
+        let mut beta = rng.gen::<f64>() * 2.0;
         let mut mw = 0.00000001;
         for &w in ws.iter() { if w > mw { mw = w; } } 
         for _ in range(0, n) {
-            beta += random::<f32>() * 2.0 * mw;
+            beta += rng.gen::<f64>() * 2.0 * mw;
             while beta > ws[index] {
                 beta -= ws[index];
                 index = (index + n + 1) % n;
@@ -1,8 +1,15 @@
-use std::iter::AdditiveIterator;
-use std::rand::{random, task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::pose::{Pose, HasPose};
+use cs373::particle::{ParticleFilter, MotionModel};
+use cs373::models::RangeModel;
+use cs373::kld::Kld;
+use cs373::metrics::{Evaluator, Run};
+use cs373::seed;
 
 #[deriving(Show,Clone)]
 struct Robot {
@@ -21,24 +28,19 @@ fn modulo(n: f32, m: f32) -> f32 {
     k
 }
 
-fn gaussian<F: Float>(mean: F, covar2: F, x: F) -> F {
-    let two_pi: F = Float::two_pi();
-    ((x - mean).powi(2) / -(covar2 + covar2)).exp() / (two_pi * covar2).sqrt()
-}
-
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
 
 impl Robot {
-    fn new(world_size: f32) -> Robot {
+    fn new<R: Rng>(world_size: f32, rng: &mut R) -> Robot {
         Robot {
             world_size: world_size,
-            x: random::<f32>() * world_size,
-            y: random::<f32>() * world_size,
-            orientation: random::<f32>() * Float::two_pi(),
+            x: rng.gen::<f32>() * world_size,
+            y: rng.gen::<f32>() * world_size,
+            orientation: rng.gen::<f32>() * Float::two_pi(),
             forward_noise: 0.0,
             turn_noise: 0.0,
             sense_noise: 0.0,
@@ -60,114 +62,107 @@ impl Robot {
         self.sense_noise = new_s_noise;
     }
 
-    fn sense(&self, landmarks: &Vec<(f32,f32)>) -> Vec<f32> {
+    fn sense<R: Rng>(&self, landmarks: &Vec<(f32,f32)>, rng: &mut R) -> Vec<f32> {
         let mut z: Vec<f32> = Vec::with_capacity(landmarks.len());
         for &(x,y) in landmarks.iter() {
             // sensed dist
             let mut dist = ((self.x - x).powi(2) + (self.y - y).powi(2)).sqrt();
             // add error
-            dist += gauss(0.0, self.sense_noise);
+            dist += gauss(0.0, self.sense_noise, rng);
             z.push(dist);
         }
         z
     }
 
-    fn travel(&self, turn: f32, forward: f32) -> Robot {
+    fn travel<R: Rng>(&self, turn: f32, forward: f32, rng: &mut R) -> Robot {
         assert!(forward >= 0.0);
 
-        let mut orientation = self.orientation + turn + gauss(0.0, self.sense_noise);
+        let mut orientation = self.orientation + turn + gauss(0.0, self.sense_noise, rng);
         orientation = modulo(orientation, Float::two_pi());
 
-        let dist = forward + gauss(0.0, self.forward_noise);
+        let dist = forward + gauss(0.0, self.forward_noise, rng);
         let mut x = self.x + orientation.cos() * dist;
         let mut y = self.y + orientation.sin() * dist;
         x = modulo(x, self.world_size);
         y = modulo(y, self.world_size);
 
-        let mut res = Robot::new(self.world_size);
+        let mut res = Robot::new(self.world_size, rng);
         res.set(x, y, orientation);
         res.set_noise(self.forward_noise, self.turn_noise, self.sense_noise);
         res
     }
+}
 
-    fn measurement_prob(&self, landmarks: &Vec<(f32,f32)>, measurement: &Vec<f32>) -> f32 {
-        assert!(landmarks.len() == measurement.len());
-        let mut prob: f32 = 1.0;
-        for i in range(0,landmarks.len()) {
-            let (x,y) = landmarks[i];
-            let dist = ((self.x - x).powi(2) + (self.y - y).powi(2)).sqrt();
-            prob = prob * gaussian(dist, self.sense_noise, measurement[i]);
-        }
-        prob
+impl HasPose for Robot {
+    fn pose(&self) -> Pose {
+        Pose::new(self.x, self.y, self.orientation)
     }
 }
 
-fn eval(r: &Robot, ps: &Vec<Robot>) -> f32 {
-    let mut sum: f32 = 0.0;
-    for p in ps.iter() {
-        assert!(r.world_size == p.world_size);
-        let dx = modulo((p.x - r.x + (r.world_size / 2.0)), r.world_size) - (r.world_size / 2.0);
-        let dy = modulo((p.y - r.y + (r.world_size / 2.0)), r.world_size) - (r.world_size / 2.0);
-        let err = (dx * dx + dy * dy).sqrt();
-        sum += err;
+// turn, then move forward
+struct Travel;
+
+impl MotionModel<Robot, (f32, f32)> for Travel {
+    fn sample<R: Rng>(&self, r: &Robot, &(turn, forward): &(f32, f32), rng: &mut R) -> Robot {
+        r.travel(turn, forward, rng)
     }
-    sum / (ps.len() as f32)
 }
 
+static RUNS: uint = 20;
+
+
 fn main() {
+    let mut rng = seed::from_env();
+    let world_size: f32 = 100.0;
+    // the torus-wrapped distance, as before; within 5 and 0.2 rad counts as found
+    let evaluator = Evaluator::new(5.0, 0.2).with_world_size(world_size);
+
+    println!("Error while applying PF:");
+    let mut runs = vec![run_filter(world_size, &evaluator, true, &mut rng)];
+    for _ in range(1, RUNS) {
+        runs.push(run_filter(world_size, &evaluator, false, &mut rng));
+    }
+    println!("Over {} runs:\n{}", RUNS, evaluator.summarize(&runs).to_json());
+}
+
+
+// One run from a random start. Errors are the particles' weighted mean distance and
+// heading difference from the robot, before the first step and after each one.
+fn run_filter<R: Rng>(world_size: f32, evaluator: &Evaluator, verbose: bool,
+        rng: &mut R) -> Run {
     let landmarks: Vec<(f32,f32)> = vec![(20.0, 20.0),
                                          (80.0, 80.0),
                                          (20.0, 80.0),
                                          (80.0, 20.0)];
 
-    let world_size: f32 = 100.0;
-    let mut myrobot = Robot::new(world_size);
-    
+    let mut myrobot = Robot::new(world_size, rng);
+
     let n: uint = 1000;
-    let mut ps = Vec::from_fn(n, |_| {
-        let mut x = Robot::new(world_size);
+    let mut filter = ParticleFilter::new(Vec::from_fn(n, |_| {
+        let mut x = Robot::new(world_size, rng);
         x.set_noise(0.05, 0.05, 5.0);
         x
-    });
-
-    println!("Error before applying PF:\n{}\nError while applying PF:", eval(&myrobot, &ps));
+    }));
+    let sensor = RangeModel::new(landmarks.clone(), 5.0);
+    // many particles while the robot could be anywhere, far fewer once they agree
+    let kld = Kld::new(2.0, 0.2).with_limits(100, n);
+
+    let mut run = Run::new();
+    run.record(evaluator.particle_error(&myrobot.pose(), filter.particles(), filter.weights()));
+    if verbose {
+        println!("{} (before)", run.position[0]);
+    }
     for _ in range(0u,10u) {
-        myrobot = myrobot.travel(0.1, 5.0);
-        let z = myrobot.sense(&landmarks);
-
-        // travel
-        for i in range(0, n) {
-            *ps.get_mut(i) = ps[i].travel(0.1, 5.0);
-        }
-
-        // weigh
-        let mut ws: Vec<f32> = Vec::with_capacity(n);
-        for i in range(0, ps.len()) {
-            ws.push(ps[i].measurement_prob(&landmarks, &z));
-        }
-
-        // normalize
-        let s: f32 = ws.iter().map(|&x| x).sum();
-
-        for i in range(0, n) {
-            *ws.get_mut(i) = ws[i] / s;
-        }
-
-        // resample
-        let mut p3: Vec<Robot> = Vec::with_capacity(n);
-        let mut index = random::<uint>() % n;
-        let mut beta = random::<f32>() * 2.0;
-        let mut mw = 0.00000001;
-        for &w in ws.iter() { if w > mw { mw = w; } } 
-        for _ in range(0, n) {
-            beta += random::<f32>() * 2.0 * mw;
-            while beta > ws[index] {
-                beta -= ws[index];
-                index = (index + n + 1) % n;
-            }
-            p3.push(ps[index].clone());
+        myrobot = myrobot.travel(0.1, 5.0, rng);
+        let z = myrobot.sense(&landmarks, rng);
+
+        let count = filter.step_kld(&Travel, &(0.1, 5.0), &sensor, &z, &kld, rng);
+        let (position, orientation) =
+            evaluator.particle_error(&myrobot.pose(), filter.particles(), filter.weights());
+        run.record((position, orientation));
+        if verbose {
+            println!("{} {} ({} particles)", position, orientation, count);
         }
-        ps = p3;
-        println!("{}", eval(&myrobot, &ps));
     }
+    run
 }
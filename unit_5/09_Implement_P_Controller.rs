@@ -1,7 +1,10 @@
-use std::rand::{task_rng};
+extern crate cs373;
+
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::seed;
 
 
 struct Robot {
@@ -49,12 +52,13 @@ impl Robot {
     }
 
 
-    fn travel(&self, steering: f32, distance: f32) -> Robot {
-        self.travel_with_extra(steering, distance, 0.001, Float::frac_pi_4())
+    fn travel<R: Rng>(&self, steering: f32, distance: f32, rng: &mut R) -> Robot {
+        self.travel_with_extra(steering, distance, 0.001, Float::frac_pi_4(), rng)
     }
 
 
-    fn travel_with_extra(&self, steering: f32, distance: f32, tolerance: f32, max_steering_angle: f32) -> Robot {
+    fn travel_with_extra<R: Rng>(&self, steering: f32, distance: f32, tolerance: f32, max_steering_angle: f32,
+            rng: &mut R) -> Robot {
         let mut steering = if steering > max_steering_angle { max_steering_angle } else { steering };
         steering = if steering < -max_steering_angle { -max_steering_angle } else { steering };
         let distance = if distance < 0.0 { 0.0 } else { distance };
@@ -67,8 +71,8 @@ impl Robot {
         res.steering_drift = self.steering_drift;
 
         // apply noise
-        let mut steering2 = gauss(steering, self.steering_noise);
-        let distance2 = gauss(distance, self.distance_noise);
+        let mut steering2 = gauss(steering, self.steering_noise, rng);
+        let distance2 = gauss(distance, self.distance_noise, rng);
 
         // apply steering drift
         steering2 += self.steering_drift;
@@ -104,7 +108,7 @@ impl Robot {
 }
 
 
-fn run(param: f32) {
+fn run<R: Rng>(param: f32, rng: &mut R) {
     let mut myrobot = Robot::new();
     myrobot.set(0.0, 1.0, 0.0);
     let speed = 1.0;
@@ -114,14 +118,15 @@ fn run(param: f32) {
     for _ in range(0u,n) {
         let crosstrack_error = myrobot.y;
         let steering = -tau * crosstrack_error;
-        myrobot = myrobot.travel(steering, speed);
+        myrobot = myrobot.travel(steering, speed, rng);
         myrobot.print();
     }
 }
 
 
 fn main() {
-    run(0.1)
+    let mut rng = seed::from_env();
+    run(0.1, &mut rng)
 }
 
 
@@ -132,8 +137,8 @@ fn modulo(n: f32, m: f32) -> f32 {
 }
 
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
@@ -4,11 +4,14 @@
 
 #![feature(tuple_indexing)]
 
+extern crate cs373;
+
 use std::iter::{AdditiveIterator};
-use std::rand::{task_rng};
+use std::rand::Rng;
 use std::rand::distributions::{Normal, IndependentSample};
 use std::num::{Float, FloatMath};
 use std::num::{FromPrimitive};
+use cs373::seed;
 
 
 struct Robot {
@@ -56,12 +59,13 @@ impl Robot {
     }
 
 
-    fn travel(&self, steering: f32, distance: f32) -> Robot {
-        self.travel_with_extra(steering, distance, 0.001, Float::frac_pi_4())
+    fn travel<R: Rng>(&self, steering: f32, distance: f32, rng: &mut R) -> Robot {
+        self.travel_with_extra(steering, distance, 0.001, Float::frac_pi_4(), rng)
     }
 
 
-    fn travel_with_extra(&self, steering: f32, distance: f32, tolerance: f32, max_steering_angle: f32) -> Robot {
+    fn travel_with_extra<R: Rng>(&self, steering: f32, distance: f32, tolerance: f32, max_steering_angle: f32,
+            rng: &mut R) -> Robot {
         let mut steering = if steering > max_steering_angle { max_steering_angle } else { steering };
         steering = if steering < -max_steering_angle { -max_steering_angle } else { steering };
         let distance = if distance < 0.0 { 0.0 } else { distance };
@@ -74,8 +78,8 @@ impl Robot {
         res.steering_drift = self.steering_drift;
 
         // apply noise
-        let mut steering2 = gauss(steering, self.steering_noise);
-        let distance2 = gauss(distance, self.distance_noise);
+        let mut steering2 = gauss(steering, self.steering_noise, rng);
+        let distance2 = gauss(distance, self.distance_noise, rng);
 
         // apply steering drift
         steering2 += self.steering_drift;
@@ -111,7 +115,8 @@ impl Robot {
 }
 
 
-fn run_with_extra((tau_p, tau_d, tau_i): (f32, f32, f32), print_flag: bool) -> f32 {
+fn run_with_extra<R: Rng>((tau_p, tau_d, tau_i): (f32, f32, f32), print_flag: bool,
+        rng: &mut R) -> f32 {
     let mut myrobot = Robot::new();
     myrobot.set(0.0, 1.0, 0.0);
     let speed = 1.0;
@@ -130,7 +135,7 @@ fn run_with_extra((tau_p, tau_d, tau_i): (f32, f32, f32), print_flag: bool) -> f
         let steering = -tau_p * cte
                      + -tau_d * cte_d
                      + -tau_i * cte_i;
-        myrobot = myrobot.travel(steering, speed);
+        myrobot = myrobot.travel(steering, speed, rng);
         if i >= n {
             err += cte.powi(2);
         }
@@ -143,31 +148,33 @@ fn run_with_extra((tau_p, tau_d, tau_i): (f32, f32, f32), print_flag: bool) -> f
 }
 
 
-fn run(params: (f32, f32, f32)) -> f32 {
-    run_with_extra(params, false)
+// every run from the same seed sees the same noise, so that the errors of different
+// parameters can be compared
+fn run(params: (f32, f32, f32), noise_seed: uint) -> f32 {
+    run_with_extra(params, false, &mut seed::seeded(noise_seed))
 }
 
 
-fn twiddle_with_extra(tol: f32) -> f32 {
+fn twiddle_with_extra(tol: f32, noise_seed: uint) -> f32 {
 
     let cvt = |v: &Vec<f32>| (v[0], v[1], v[2]);
 
     let mut d: Vec<f32> = vec![1.0, 1.0, 1.0];
     let mut p: Vec<f32> = vec![0.0, 0.0, 0.0];
 
-    let mut best_error = run_with_extra(cvt(&p), true);
+    let mut best_error = run_with_extra(cvt(&p), true, &mut seed::seeded(noise_seed));
 
     while d.iter().map(|&x| x).sum() > tol {
         for i in range(0, p.len()) {
             *p.get_mut(i) += d[i];
-            let mut err = run_with_extra(cvt(&p), true);
+            let mut err = run_with_extra(cvt(&p), true, &mut seed::seeded(noise_seed));
 
             if err < best_error {
                 best_error = err;
                 *d.get_mut(i) = d[i] * 1.1;
             } else {
                 *p.get_mut(i) = p[i] - 2.0 * d[i];
-                err = run_with_extra(cvt(&p), true);
+                err = run_with_extra(cvt(&p), true, &mut seed::seeded(noise_seed));
                 if err < best_error {
                     best_error = err;
                     *d.get_mut(i) = d[i] * 1.1;
@@ -179,18 +186,18 @@ fn twiddle_with_extra(tol: f32) -> f32 {
         }
     }
 
-    run(cvt(&p))
+    run(cvt(&p), noise_seed)
 }
 
 
-fn twiddle() -> f32 {
-    twiddle_with_extra(0.2)
+fn twiddle(noise_seed: uint) -> f32 {
+    twiddle_with_extra(0.2, noise_seed)
 }
 
 
 fn main() {
-    println!("{:.15f}", twiddle());
-    // run_with_extra((0.2, 3.0, 0.004), true);
+    println!("{:.15f}", twiddle(seed::seed_from_env()));
+    // run_with_extra((0.2, 3.0, 0.004), true, &mut seed::from_env());
 }
 
 
@@ -201,8 +208,8 @@ fn modulo(n: f32, m: f32) -> f32 {
 }
 
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut task_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }
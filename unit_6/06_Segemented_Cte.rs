@@ -1,7 +1,11 @@
-extern crate rand;
+extern crate cs373;
 
 //use std::iter::AdditiveIterator;
-use rand::{rthread_rng, random};
+use std::rand::Rng;
+use cs373::pose::{Pose, HasPose};
+use cs373::particle::{ParticleFilter, MotionModel};
+use cs373::models::GpsModel;
+use cs373::seed;
 //use std::rand::distributions::{Normal, IndependentSample};
 //use std::num::{Float, FloatMath};
 //use std::num::{FromPrimitive};
@@ -91,7 +95,12 @@ struct Particles {
     steering_noise: f32,
     distance_noise: f32,
     measurement_noise: f32,
-    data: Vec<Robot>,
+    filter: ParticleFilter<Robot>,
+}
+
+// (steering, speed) on a grid
+struct Drive<'a> {
+    grid: &'a Grid,
 }
 
 impl Plan {
@@ -281,11 +290,11 @@ impl Robot {
         dist < threshold
     }
     //--
-    fn travel(&self, grid: &Grid, steering: f32, distance: f32) -> Robot {
-        self.travel_extra(grid, steering, distance, 0.001, Float::frac_pi_4())
+    fn travel<R: Rng>(&self, grid: &Grid, steering: f32, distance: f32, rng: &mut R) -> Robot {
+        self.travel_extra(grid, steering, distance, 0.001, Float::frac_pi_4(), rng)
     }
-    fn travel_extra(&self, _: &Grid, steering: f32, distance: f32, tolerance: f32,
-            max_steering_angle: f32) -> Robot {
+    fn travel_extra<R: Rng>(&self, _: &Grid, steering: f32, distance: f32, tolerance: f32,
+            max_steering_angle: f32, rng: &mut R) -> Robot {
         let steering =
             if steering > max_steering_angle {
                 max_steering_angle
@@ -299,8 +308,8 @@ impl Robot {
         let mut res = self.clone();
         res.num_steps = self.num_steps + 1;
         // apply noise
-        let steering2 = gauss(steering, self.steering_noise);
-        let distance2 = gauss(distance, self.distance_noise);
+        let steering2 = gauss(steering, self.steering_noise, rng);
+        let distance2 = gauss(distance, self.distance_noise, rng);
         // execute motion
         let turn = steering2.tan() * distance2 / res.length;
         if turn.abs() < tolerance {
@@ -320,17 +329,8 @@ impl Robot {
 
         res
     }
-    fn sense(&self) -> (f32, f32) {
-        (gauss(self.x, self.measurement_noise), gauss(self.y, self.measurement_noise))
-    }
-    fn measurement_prob(&self, measurement: (f32, f32)) -> f32 {
-        let error_x = measurement.0 - self.x;
-        let error_y = measurement.1 - self.y;
-        let mut error: f32 = (-(error_x.powi(2)) / self.measurement_noise.powi(2) / 2.0).exp()
-                      / (self.measurement_noise.powi(2) * Float::two_pi()).sqrt();
-        error *= (-(error_y.powi(2)) / self.measurement_noise.powi(2) / 2.0).exp()
-                      / (self.measurement_noise.powi(2) * Float::two_pi()).sqrt();
-        error
+    fn sense<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        (gauss(self.x, self.measurement_noise, rng), gauss(self.y, self.measurement_noise, rng))
     }
     fn print(&self) {
         print!("[x={:.5} y={:.5} orient={:.5}]", self.x, self.y, self.orientation);
@@ -349,63 +349,47 @@ impl Particles {
             steering_noise: steering_noise,
             distance_noise: distance_noise,
             measurement_noise: measurement_noise,
-            data: vec![{
+            filter: ParticleFilter::new(vec![{
                     let mut r = Robot::new();
                     r.set(x, y, theta);
                     r.set_noise(steering_noise, distance_noise, measurement_noise);
                     r
-                }, n],
+                }; n]),
         }
     }
+    // the most weighted mode of the particles
     fn get_position(&self) -> (f32, f32, f32) {
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut orientation = 0.0;
-        for i in 0..self.n {
-            x += self.data[i].x;
-            y += self.data[i].y;
-            orientation += modulo(self.data[i].orientation
-                                        - self.data[0].orientation
-                                        + Float::pi(),
-                                 Float::two_pi())
-                           + self.data[0].orientation - Float::pi();
-        }
-        let n: f32 = self.n as f32;
-        (x / n, y / n, orientation / n)
+        let best = &self.filter.hypotheses(1.0, 0.5)[0];
+        (best.pose.x, best.pose.y, best.pose.orientation)
     }
-    fn travel(&mut self, grid: &Grid, steer: f32, speed: f32) {
-        self.data = Vec::from_fn(self.data.len(), |i: usize| {
-            self.data[i].travel(grid, steer, speed)
-        });
+    fn travel<R: Rng>(&mut self, grid: &Grid, steer: f32, speed: f32, rng: &mut R) {
+        self.filter.predict(&Drive { grid: grid }, &(steer, speed), rng);
     }
-    fn sense(&mut self, z: (f32, f32)) {
-        let ws = Vec::from_fn(self.n, |i: usize| {
-                self.data[i].measurement_prob(z)
-            });
-        let mut p3 = Vec::with_capacity(self.n);
-        let mut idx = random::<usize>() % self.n;
-        let mut beta = 0.0;
-        let mut mw = 0.0;
-        for &w in ws.iter() { if w > mw { mw = w; } }
-        for _ in 0..self.n {
-            beta += random::<f32>() * 2.0 * mw;
-            while beta > ws[idx] {
-                beta -= ws[idx];
-                idx = (idx + 1) % self.n;
-            }
-            p3.push(self.data[idx].clone());
-        }
-        self.data = p3;
+    fn sense<R: Rng>(&mut self, z: (f32, f32), rng: &mut R) {
+        self.filter.update(&GpsModel::new(self.measurement_noise), &z);
+        self.filter.resample_if_needed(rng);
+    }
+}
+
+impl HasPose for Robot {
+    fn pose(&self) -> Pose {
+        Pose::new(self.x, self.y, self.orientation)
+    }
+}
+
+impl<'a> MotionModel<Robot, (f32, f32)> for Drive<'a> {
+    fn sample<R: Rng>(&self, r: &Robot, &(steer, speed): &(f32, f32), rng: &mut R) -> Robot {
+        r.travel(self.grid, steer, speed, rng)
     }
 }
 
-fn run(grid: &Grid, goal: (usize,usize), spath: &Vec<Vec<f32>>, params: (f32,f32))
-        -> (bool, usize, usize) {
-    run_extra(grid, goal, spath, params, false, 0.1, 1000)
+fn run<R: Rng>(grid: &Grid, goal: (usize,usize), spath: &Vec<Vec<f32>>, params: (f32,f32),
+        rng: &mut R) -> (bool, usize, usize) {
+    run_extra(grid, goal, spath, params, false, 0.1, 1000, rng)
 }
 
-fn run_extra(grid: &Grid, goal: (usize,usize), spath: &Vec<Vec<f32>>, params: (f32,f32),
-        print_flag: bool, speed: f32, timeout: usize) -> (bool, usize, usize) {
+fn run_extra<R: Rng>(grid: &Grid, goal: (usize,usize), spath: &Vec<Vec<f32>>, params: (f32,f32),
+        print_flag: bool, speed: f32, timeout: usize, rng: &mut R) -> (bool, usize, usize) {
     let mut myrobot = Robot::new();
     myrobot.set(0.0, 0.0, 0.0);
     myrobot.set_noise(STEERING_NOISE, DISTANCE_NOISE, MEASUREMENT_NOISE);
@@ -429,10 +413,10 @@ fn run_extra(grid: &Grid, goal: (usize,usize), spath: &Vec<Vec<f32>>, params: (f
         }
         cte_d += cte_p;
         let steer = -(params.0 * cte_p) - (params.1 * cte_d);
-        myrobot = myrobot.travel(grid, steer, speed);
-        filter.travel(grid, steer, speed);
-        let z = myrobot.sense();
-        filter.sense(z);
+        myrobot = myrobot.travel(grid, steer, speed, rng);
+        filter.travel(grid, steer, speed, rng);
+        let z = myrobot.sense(rng);
+        filter.sense(z, rng);
         if !myrobot.check_collision(grid) {
             println!("##### Collision ####");
         }
@@ -450,32 +434,35 @@ fn main() {
     println!("{}",
             main_fn(&GRID, INIT, GOAL,
                     STEERING_NOISE, DISTANCE_NOISE, MEASUREMENT_NOISE,
-                    WEIGHT_DATA, WEIGHT_SMOOTH, P_GAIN, D_GAIN))
+                    WEIGHT_DATA, WEIGHT_SMOOTH, P_GAIN, D_GAIN, &mut seed::from_env()))
 }
 
-fn main_fn(grid: &Grid, init: (usize, usize), goal: (usize, usize),
+fn main_fn<R: Rng>(grid: &Grid, init: (usize, usize), goal: (usize, usize),
         _: f32, _: f32, _: f32,
-        weight_data: f32, weight_smooth: f32, p_gain: f32, d_gain: f32) -> (bool, usize, usize) {
+        weight_data: f32, weight_smooth: f32, p_gain: f32, d_gain: f32,
+        rng: &mut R) -> (bool, usize, usize) {
     let mut plan = Plan::new(grid, init, goal);
     plan.astar();
     plan.smooth_extra(weight_data, weight_smooth, 0.000001);
     for i in 0..plan.spath.len() {
         println!("{} -> {}", plan.path[i], plan.spath[i]);
     }
-    run(grid, goal, &plan.spath, (p_gain, d_gain))
+    run(grid, goal, &plan.spath, (p_gain, d_gain), rng)
 }
 
 #[allow(dead_code)]
-fn twiddle(init_params: &Vec<f32>) -> Vec<f32> {
+// Run j of each evaluation is seeded with noise_seed + j, so every set of parameters is
+// scored against the same noise.
+fn twiddle(init_params: &Vec<f32>, noise_seed: usize) -> Vec<f32> {
     let n_params = init_params.len();
     let mut dparams: Vec<f32> = Vec::from_elem(n_params, 1.0);
     let mut params = init_params.clone();
     let k = 10;
     let mut best_error = 0.0;
-    for _ in 0..k {
+    for j in 0..k {
         let ret = main_fn(&GRID, INIT, GOAL,
                 STEERING_NOISE, DISTANCE_NOISE, MEASUREMENT_NOISE,
-                params[0], params[1], params[2], params[3]);
+                params[0], params[1], params[2], params[3], &mut seed::seeded(noise_seed + j));
         best_error += if ret.0 { ret.1 as f32 * 100.0 + ret.2 as f32 } else { 99999.0 };
     }
     best_error /= k as f32;
@@ -488,7 +475,8 @@ fn twiddle(init_params: &Vec<f32>) -> Vec<f32> {
             for j in 0..k {
                 let ret = main_fn(&GRID, INIT, GOAL,
                                 STEERING_NOISE, DISTANCE_NOISE, MEASUREMENT_NOISE,
-                                params[0], params[1], params[2], params[3]);
+                                params[0], params[1], params[2], params[3],
+                                &mut seed::seeded(noise_seed + j));
                 err += if ret.0 { ret.1 as f32 * 100.0 + ret.2 as f32 } else { 99999.0 };
             }
             println!("{}", err / k as f32);
@@ -524,8 +512,8 @@ fn modulo(n: f32, m: f32) -> f32 {
     k
 }
 
-fn gauss(mean: f32, covar2: f32) -> f32 {
+fn gauss<R: Rng>(mean: f32, covar2: f32, rng: &mut R) -> f32 {
     let cvt_covar2: f64 = FromPrimitive::from_f32(covar2).unwrap();
     let cvt_mean: f64 = FromPrimitive::from_f32(mean).unwrap();
-    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(&mut thread_rng())).unwrap()
+    FromPrimitive::from_f64(Normal::new(cvt_mean, cvt_covar2).ind_sample(rng)).unwrap()
 }